@@ -0,0 +1,69 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Result as IoResult;
+use std::path::Path;
+
+/// Known good answers for a single day, as they are displayed by `Sol1`/`Sol2`.
+#[derive(Deserialize)]
+pub struct DayAnswers {
+    p1: Option<String>,
+    p2: Option<String>,
+}
+
+impl DayAnswers {
+    fn part(&self, part: u32) -> Option<&str> {
+        match part {
+            1 => self.p1.as_deref(),
+            2 => self.p2.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// The expected answers registry, stored in `answers.toml` by year as
+///
+/// ```toml
+/// [2021.day01]
+/// p1 = "1139"
+/// p2 = "1103"
+/// ```
+pub struct Answers(HashMap<String, HashMap<String, DayAnswers>>);
+
+impl Answers {
+    pub fn load<P: AsRef<Path>>(path: P) -> IoResult<Answers> {
+        Ok(Answers(load(path)?))
+    }
+
+    pub fn get(&self, year: u32, day: u32, part: u32) -> Option<&str> {
+        let answers = self
+            .0
+            .get(&year.to_string())?
+            .get(&format!("day{:02}", day))?;
+        answers.part(part)
+    }
+}
+
+/// The expected answers for a day's example inputs, stored next to them in
+/// the [`example_dir`](crate::example_dir) by example name as
+///
+/// ```toml
+/// [example]
+/// p1 = "7"
+/// p2 = "5"
+/// ```
+pub struct Examples(HashMap<String, DayAnswers>);
+
+impl Examples {
+    pub fn load<P: AsRef<Path>>(path: P) -> IoResult<Examples> {
+        Ok(Examples(load(path)?))
+    }
+
+    pub fn get(&self, name: &str, part: u32) -> Option<&str> {
+        self.0.get(name)?.part(part)
+    }
+}
+
+fn load<T: serde::de::DeserializeOwned, P: AsRef<Path>>(path: P) -> IoResult<T> {
+    let s = std::fs::read_to_string(path)?;
+    toml::from_str(&s).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
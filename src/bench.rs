@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::io::Result as IoResult;
+use std::path::Path;
+use std::time::Duration;
+
+/// How many times to run each part.
+pub struct BenchOpts {
+    /// Untimed runs to warm up caches and the allocator.
+    pub warmup: usize,
+    pub runs: usize,
+}
+
+/// Timings for one day: a single parse, then every timed run of each part.
+pub struct Timings {
+    pub parse: Duration,
+    pub parts: Vec<(u32, Vec<Duration>)>,
+}
+
+/// Summary of the timed runs of a single part. Times are in nanoseconds so
+/// that saved results are easy to read and compare from other tools.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub year: u32,
+    pub day: u32,
+    pub part: u32,
+    pub runs: usize,
+    pub min_ns: f64,
+    pub median_ns: f64,
+    pub mean_ns: f64,
+    pub stddev_ns: f64,
+}
+
+impl Stats {
+    pub fn new(year: u32, day: u32, part: u32, times: &[Duration]) -> Stats {
+        let mut ns = times
+            .iter()
+            .map(|t| t.as_nanos() as f64)
+            .collect::<Vec<_>>();
+        ns.sort_by(|a, b| a.total_cmp(b));
+
+        let n = ns.len();
+        let (min, median, mean, stddev) = if n == 0 {
+            (0.0, 0.0, 0.0, 0.0)
+        } else {
+            let median = if n % 2 == 0 {
+                (ns[n / 2 - 1] + ns[n / 2]) / 2.0
+            } else {
+                ns[n / 2]
+            };
+            let mean = ns.iter().sum::<f64>() / n as f64;
+            // Sample standard deviation, a single run has none.
+            let var = if n > 1 {
+                ns.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+            } else {
+                0.0
+            };
+            (ns[0], median, mean, var.sqrt())
+        };
+
+        Stats {
+            year,
+            day,
+            part,
+            runs: n,
+            min_ns: min,
+            median_ns: median,
+            mean_ns: mean,
+            stddev_ns: stddev,
+        }
+    }
+
+    /// Relative change of the median against an earlier run, in percent.
+    pub fn change(&self, baseline: &Stats) -> Option<f64> {
+        if baseline.median_ns == 0.0 {
+            return None;
+        }
+        Some((self.median_ns - baseline.median_ns) / baseline.median_ns * 100.0)
+    }
+}
+
+pub fn duration(ns: f64) -> Duration {
+    Duration::from_nanos(ns.round() as u64)
+}
+
+pub fn save<P: AsRef<Path>>(path: P, stats: &[Stats]) -> IoResult<()> {
+    let json = serde_json::to_string_pretty(stats)?;
+    std::fs::write(path, json + "\n")
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> IoResult<Vec<Stats>> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let times = [4, 1, 3, 2].map(Duration::from_nanos);
+        let s = Stats::new(2021, 1, 2, &times);
+        assert_eq!(s.runs, 4);
+        assert_eq!(s.min_ns, 1.0);
+        assert_eq!(s.median_ns, 2.5);
+        assert_eq!(s.mean_ns, 2.5);
+        assert!((s.stddev_ns - 1.2909944).abs() < 1e-6);
+
+        let slower = Stats::new(2021, 1, 2, &[Duration::from_nanos(5)]);
+        assert_eq!(slower.stddev_ns, 0.0);
+        assert_eq!(slower.change(&s), Some(100.0));
+    }
+}
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 impl Day for Solution {
@@ -10,11 +10,17 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        r.lines().map(|v| Ok(v?.parse::<u32>().unwrap())).collect()
+        r.lines()
+            .enumerate()
+            .map(|(i, l)| {
+                let l = l?;
+                Ok(ParseError::parse(i, &l, &l)?)
+            })
+            .collect()
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         v.windows(2).map(|vs| vs[1] > vs[0]).filter(|v| *v).count()
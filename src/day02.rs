@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub enum Dir {
     Forward,
@@ -21,22 +21,25 @@ impl Day for Solution {
     type Sol1 = i32;
     type Sol2 = i32;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         r.lines()
-            .map(|l| {
+            .enumerate()
+            .map(|(i, l)| {
                 let l = l?;
-                let mut split = l.split(" ");
+                let (dir, v) = l
+                    .split_once(' ')
+                    .ok_or_else(|| ParseError::new(i, 0, "expected a direction and distance"))?;
 
-                let dir = match split.next().unwrap() {
+                let dir = match dir {
                     "forward" => Dir::Forward,
                     "down" => Dir::Down,
                     "up" => Dir::Up,
-                    _ => panic!(),
+                    _ => return Err(ParseError::at(i, &l, dir, "unknown direction").into()),
                 };
-                let v = split.next().unwrap().parse::<i32>().unwrap();
+                let v = ParseError::parse(i, &l, v)?;
                 Ok(Cmd { dir, val: v })
             })
             .collect()
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 impl Day for Solution {
@@ -10,11 +10,20 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        r.lines().map(|l| Ok(l?.to_string())).collect()
+        r.lines()
+            .enumerate()
+            .map(|(i, l)| {
+                let l = l?;
+                if let Some(c) = l.find(|c| c != '0' && c != '1') {
+                    return Err(ParseError::new(i, c, "expected a binary digit").into());
+                }
+                Ok(l)
+            })
+            .collect()
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         let len = v.len();
@@ -25,7 +34,7 @@ impl Day for Solution {
                 .iter()
                 .filter(|x| x.chars().nth(i).unwrap() == '1')
                 .count();
-            println!("{} {}", count, len);
+            log::trace!("{} {}", count, len);
             if count >= len - count {
                 gamma.push_str("1");
             } else {
@@ -33,9 +42,9 @@ impl Day for Solution {
             }
         }
         let gamma = usize::from_str_radix(&gamma, 2).unwrap();
-        println!("gamma: {} {:b}", gamma, gamma);
+        log::debug!("gamma: {} {:b}", gamma, gamma);
         let epsilon = (!gamma) & ((1 << bits) - 1);
-        println!("epsilon {} {:b}", epsilon, epsilon);
+        log::debug!("epsilon {} {:b}", epsilon, epsilon);
         epsilon * gamma
     }
     fn p2(v: &Self::Input) -> Self::Sol2 {
@@ -46,7 +55,7 @@ impl Day for Solution {
                     .iter()
                     .filter(|x| x.chars().nth(i).unwrap() == '1')
                     .count();
-                println!("{} {}", count, v.len());
+                log::trace!("{} {}", count, v.len());
                 let len = v.len();
                 let bit = if count >= len - count { most } else { !most };
                 let bit = if bit { '1' } else { '0' };
@@ -61,7 +70,7 @@ impl Day for Solution {
         let bits = v[0].len();
         let o2 = filter_common(v, bits, true);
         let co2 = filter_common(v, bits, false);
-        println!("o2: {}, co2 {}", o2, co2);
+        log::debug!("o2: {}, co2 {}", o2, co2);
         o2 * co2
     }
 }
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 #[derive(Debug, Clone)]
 pub struct Bingo {
@@ -39,32 +39,43 @@ impl Day for Solution {
     type Sol1 = u32;
     type Sol2 = u32;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines();
-        let input: Vec<_> = lines
-            .by_ref()
-            .next()
-            .unwrap()?
+        let mut lines = r.lines().enumerate();
+        let draws = match lines.next() {
+            Some((_, l)) => l?,
+            None => return Err(ParseError::new(0, 0, "missing bingo numbers").into()),
+        };
+        let input = draws
             .split(',')
-            .map(|v| v.parse::<u32>().unwrap())
-            .collect();
+            .map(|v| ParseError::parse(0, &draws, v))
+            .collect::<Result<Vec<u32>, _>>()?;
         lines.next();
 
         let mut boards = vec![];
         loop {
-            let board: Vec<Vec<_>> = lines
+            let mut last = 0;
+            let board = lines
                 .by_ref()
                 .take(5)
-                .map(|x| {
-                    x.unwrap()
+                .map(|(i, l)| {
+                    let l = l?;
+                    last = i;
+                    let row = l
                         .split_whitespace()
-                        .map(|v| v.parse::<u32>().unwrap())
-                        .collect()
+                        .map(|v| ParseError::parse(i, &l, v))
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    if row.len() != 5 {
+                        return Err(ParseError::new(i, 0, "expected 5 numbers in a row").into());
+                    }
+                    Ok(row)
                 })
-                .collect();
+                .collect::<Result<Vec<_>, DayError>>()?;
+            if board.len() != 5 {
+                return Err(ParseError::new(last, 0, "expected 5 rows in a board").into());
+            }
 
             boards.push(Board { squares: board });
             if lines.next().is_none() {
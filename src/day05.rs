@@ -1,8 +1,8 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 use cgmath::Point2;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 fn fill_line(grid: &mut HashMap<Point2<i32>, usize>, (p1, p2): (Point2<i32>, Point2<i32>)) {
     let v = (p1 - p2).map(|c| if c == 0 { 0 } else { c / c.abs() });
@@ -25,18 +25,28 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         r.lines()
-            .map(|l| {
+            .enumerate()
+            .map(|(i, l)| {
                 let l = l?;
                 let v = l
                     .split(" -> ")
-                    .flat_map(|x| x.split(',').map(|x| x.parse().unwrap()))
-                    .collect::<Vec<_>>();
-                Ok((Point2::new(v[0], v[1]), Point2::new(v[2], v[3])))
+                    .flat_map(|x| x.split(','))
+                    .map(|x| ParseError::parse(i, &l, x))
+                    .collect::<Result<Vec<i32>, _>>()?;
+                if v.len() != 4 {
+                    return Err(ParseError::new(i, 0, "expected x1,y1 -> x2,y2").into());
+                }
+                let (p1, p2) = (Point2::new(v[0], v[1]), Point2::new(v[2], v[3]));
+                let d = p1 - p2;
+                if d.x != 0 && d.y != 0 && d.x.abs() != d.y.abs() {
+                    return Err(ParseError::new(i, 0, "line is not straight or diagonal").into());
+                }
+                Ok((p1, p2))
             })
             .collect()
     }
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 impl Day for Solution {
@@ -10,16 +10,22 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines()
-            .next()
-            .unwrap()?
-            .split(",")
-            .map(|x| x.parse::<usize>().unwrap())
-            .collect())
+        let l = r.lines().next().unwrap_or_else(|| Ok(String::new()))?;
+        let fish = l
+            .split(',')
+            .map(|x| {
+                let timer = ParseError::parse(0, &l, x)?;
+                if timer > 8 {
+                    return Err(ParseError::at(0, &l, x, "timer must be between 0 and 8"));
+                }
+                Ok(timer)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(fish)
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         let mut v = v.clone();
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 impl Day for Solution {
@@ -10,16 +10,16 @@ impl Day for Solution {
     type Sol1 = i32;
     type Sol2 = i32;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines()
-            .next()
-            .unwrap()?
-            .split(",")
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect())
+        let l = r.lines().next().unwrap_or_else(|| Ok(String::new()))?;
+        let crabs = l
+            .split(',')
+            .map(|x| ParseError::parse(0, &l, x))
+            .collect::<Result<_, _>>()?;
+        Ok(crabs)
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         let max = *v.iter().max().unwrap();
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 
@@ -22,27 +22,33 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         r.lines()
-            .map(|l| {
+            .enumerate()
+            .map(|(i, l)| {
                 let l = l?;
-                let sides = l.split("|").collect::<Vec<_>>();
+                if let Some(c) = l.find(|c: char| !matches!(c, 'a'..='g' | ' ' | '|')) {
+                    return Err(ParseError::new(i, c, "expected a segment from a to g").into());
+                }
+                let (signals, output) = l
+                    .split_once('|')
+                    .ok_or_else(|| ParseError::new(i, 0, "expected signals | output"))?;
                 let seven_seg = SevenSeg {
-                    signals: sides[0]
+                    signals: signals
                         .split_whitespace()
                         .map(|x| x.chars().collect())
                         .collect::<Vec<_>>()
                         .try_into()
-                        .unwrap(),
-                    output: sides[1]
+                        .map_err(|_| ParseError::at(i, &l, signals, "expected 10 signals"))?,
+                    output: output
                         .split_whitespace()
-                        .map(|x| sort_string(x))
+                        .map(sort_string)
                         .collect::<Vec<_>>()
                         .try_into()
-                        .unwrap(),
+                        .map_err(|_| ParseError::at(i, &l, output, "expected 4 outputs"))?,
                 };
                 Ok(seven_seg)
             })
@@ -1,71 +1,43 @@
+use crate::error::DayError;
+use crate::grid::Grid;
 use crate::Day;
+use cgmath::Point2;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
-fn get_val(arr: &Vec<Vec<usize>>, x: i32, y: i32) -> Option<&usize> {
-    if x < 0 || y < 0 {
-        return None;
-    } else {
-        arr.get(y as usize)
-            .and_then(|row: &Vec<usize>| row.get(x as usize))
-    }
-}
-
-fn lows(v: &Vec<Vec<usize>>) -> Vec<(usize, usize)> {
-    let mut low = vec![];
-    for y in 0..v.len() as i32 {
-        let row = &v[y as usize];
-        for x in 0..row.len() as i32 {
-            let search = [
-                get_val(&v, x - 1, y),
-                get_val(&v, x, y + 1),
-                get_val(&v, x, y - 1),
-                get_val(&v, x + 1, y),
-            ];
-            let m = search.into_iter().flat_map(|x| x).min();
-
-            if m.unwrap() > &v[y as usize][x as usize] {
-                low.push((x as usize, y as usize));
-            }
-        }
-    }
-    low
+fn lows(v: &Grid<usize>) -> Vec<Point2<i32>> {
+    v.iter()
+        .filter(|(p, h)| v.neighbours4(*p).all(|n| v[n] > **h))
+        .map(|(p, _)| p)
+        .collect()
 }
 
 pub struct Solution {}
 impl Day for Solution {
     const DAY: u32 = 9;
-    type Input = Vec<Vec<usize>>;
+    type Input = Grid<usize>;
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(mut r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        r.lines()
-            .map(|l| {
-                Ok(l?
-                    .chars()
-                    .map(|x| (x.to_string().parse().unwrap()))
-                    .collect())
-            })
-            .collect()
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        let digit = |c: char| c.to_digit(10).map(|d| d as usize).ok_or("expected a digit");
+        Ok(Grid::from_chars(&s, digit)?)
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
-        lows(v)
-            .into_iter()
-            .map(|(x, y)| get_val(v, x as i32, y as i32).unwrap() + 1)
-            .sum()
+        lows(v).into_iter().map(|p| v[p] + 1).sum()
     }
     fn p2(v: &Self::Input) -> Self::Sol2 {
         let lows = lows(v);
 
         let mut basins = lows
             .into_iter()
-            .map(|(x, y)| {
-                let mut search_basin = vec![(x, y)];
+            .map(|p| {
+                let mut search_basin = vec![p];
                 let mut searched = HashSet::new();
                 while let Some(s) = search_basin.pop() {
                     if searched.contains(&s) {
@@ -73,21 +45,9 @@ impl Day for Solution {
                     } else {
                         searched.insert(s);
                     }
-                    let l = get_val(v, s.0 as i32, s.1 as i32).unwrap();
+                    let l = v[s];
 
-                    let search = [
-                        (s.0 as i32 - 1, s.1 as i32),
-                        (s.0 as i32, s.1 as i32 + 1),
-                        (s.0 as i32, s.1 as i32 - 1),
-                        (s.0 as i32 + 1, s.1 as i32),
-                    ];
-                    search_basin.extend(
-                        search
-                            .iter()
-                            .flat_map(|s| get_val(v, s.0 as i32, s.1 as i32).map(|v| (s, v)))
-                            .filter(|(_, v)| **v < 9 && *v > l)
-                            .map(|(p, _)| (p.0 as usize, p.1 as usize)),
-                    );
+                    search_basin.extend(v.neighbours4(s).filter(|n| v[*n] < 9 && v[*n] > l));
                 }
                 searched.into_iter().collect::<Vec<_>>()
             })
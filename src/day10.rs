@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 #[derive(Debug)]
 enum Chunk {
@@ -49,11 +49,20 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        r.lines().map(|l| Ok(l?.to_string())).collect()
+        r.lines()
+            .enumerate()
+            .map(|(i, l)| {
+                let l = l?;
+                if let Some(c) = l.find(|c| !"<>{}()[]".contains(c)) {
+                    return Err(ParseError::new(i, c, "expected a bracket").into());
+                }
+                Ok(l)
+            })
+            .collect()
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         v.iter()
@@ -94,7 +103,7 @@ impl Day for Solution {
             })
             .collect::<Vec<_>>();
         scores.sort();
-        println!("{:?}", scores);
+        log::debug!("{:?}", scores);
         scores[scores.len() / 2]
     }
 }
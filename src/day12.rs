@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -13,9 +13,13 @@ pub enum Node {
 }
 
 impl FromStr for Node {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = s.find(|c: char| !c.is_ascii_alphabetic()) {
+            return Err(ParseError::new(0, c, "cave names must be letters"));
+        }
         match s {
+            "" => Err(ParseError::new(0, 0, "missing cave name")),
             "start" => Ok(Node::Start),
             "end" => Ok(Node::End),
             s if s.chars().all(|c| c.is_uppercase()) => Ok(Node::BigCave(s.to_string())),
@@ -85,15 +89,19 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         r.lines()
-            .map(|l| {
+            .enumerate()
+            .map(|(i, l)| {
                 let l = l?;
-                let (n1, n2) = l.split_once('-').unwrap();
-                Ok((Node::from_str(n1).unwrap(), Node::from_str(n2).unwrap()))
+                let (n1, n2) = l
+                    .split_once('-')
+                    .ok_or_else(|| ParseError::new(i, 0, "expected cave-cave"))?;
+                let node = |n| Node::from_str(n).map_err(|e| e.within(i, &l, n));
+                Ok((node(n1)?, node(n2)?))
             })
             .collect()
     }
@@ -1,7 +1,7 @@
-use crate::Day;
+use crate::error::{DayError, ParseError};
+use crate::{ocr, Day};
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub enum Fold {
     X(usize),
@@ -53,36 +53,42 @@ impl Day for Solution {
     const DAY: u32 = 13;
     type Input = Manual;
     type Sol1 = usize;
-    type Sol2 = usize;
+    type Sol2 = String;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines();
-        let dots = lines
-            .by_ref()
-            .map(|l| l.unwrap())
-            .take_while(|l| l != "")
-            .map(|l| {
-                let (a, b) = l.split_once(',').unwrap();
-                (a.parse::<usize>().unwrap(), b.parse::<usize>().unwrap())
-            })
-            .collect();
+        let mut lines = r.lines().enumerate();
+        let mut dots = vec![];
+        for (i, l) in lines.by_ref() {
+            let l = l?;
+            if l.is_empty() {
+                break;
+            }
+            let (a, b) = l
+                .split_once(',')
+                .ok_or_else(|| ParseError::new(i, 0, "expected x,y"))?;
+            dots.push((ParseError::parse(i, &l, a)?, ParseError::parse(i, &l, b)?));
+        }
 
         let folds = lines
-            .map(|l| l.unwrap())
-            .map(|l| {
-                let suffix = l.strip_prefix("fold along ").unwrap();
-                let (axis, value) = suffix.split_once("=").unwrap();
-                let value = value.parse::<usize>().unwrap();
+            .map(|(i, l)| {
+                let l = l?;
+                let suffix = l
+                    .strip_prefix("fold along ")
+                    .ok_or_else(|| ParseError::new(i, 0, "expected \"fold along\""))?;
+                let (axis, value) = suffix
+                    .split_once('=')
+                    .ok_or_else(|| ParseError::at(i, &l, suffix, "expected axis=value"))?;
+                let value = ParseError::parse(i, &l, value)?;
                 match axis {
-                    "x" => Fold::X(value),
-                    "y" => Fold::Y(value),
-                    _ => panic!(),
+                    "x" => Ok(Fold::X(value)),
+                    "y" => Ok(Fold::Y(value)),
+                    _ => Err(ParseError::at(i, &l, axis, "unknown fold axis").into()),
                 }
             })
-            .collect();
+            .collect::<Result<_, DayError>>()?;
 
         Ok(Manual { dots, folds })
     }
@@ -92,17 +98,9 @@ impl Day for Solution {
     }
     fn p2(manual: &Self::Input) -> Self::Sol2 {
         let paper = manual.folds(0..manual.folds.len());
-
-        let width = paper.iter().map(|(x, _y)| x).max().unwrap();
-        let height = paper.iter().map(|(_x, y)| y).max().unwrap();
-
-        for y in 0..=*height {
-            for x in 0..=*width {
-                print!("{}", if paper.contains(&(x, y)) { '#' } else { '.' })
-            }
-            println!()
-        }
-        panic!("Answer in text above.")
+        log::debug!("\n{}", ocr::render(&paper));
+        ocr::read(&paper)
+            .unwrap_or_else(|| panic!("Unable to read the letters in\n{}", ocr::render(&paper)))
     }
 }
 
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Polymer {
     template: String,
@@ -82,23 +82,30 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines().map(|l| l.unwrap());
+        let mut lines = r.lines().enumerate();
 
-        let template = lines.by_ref().next().unwrap();
-        lines.by_ref().next();
+        let template = match lines.next() {
+            Some((_, l)) => l?,
+            None => return Err(ParseError::new(0, 0, "missing polymer template").into()),
+        };
+        lines.next();
         let pairs = lines
-            .map(|l| {
-                let (l, r) = l.split_once(" -> ").unwrap();
-                (
-                    (l.chars().nth(0).unwrap(), l.chars().nth(1).unwrap()),
-                    r.chars().next().unwrap(),
-                )
+            .map(|(i, l)| {
+                let l = l?;
+                let (pair, insert) = l
+                    .split_once(" -> ")
+                    .ok_or_else(|| ParseError::new(i, 0, "expected AB -> C"))?;
+                match (&pair.chars().collect::<Vec<_>>()[..], insert.chars().next()) {
+                    (&[a, b], Some(c)) => Ok(((a, b), c)),
+                    (&[_, _], None) => Err(ParseError::at(i, &l, insert, "missing element").into()),
+                    _ => Err(ParseError::at(i, &l, pair, "expected a pair of elements").into()),
+                }
             })
-            .collect();
+            .collect::<Result<_, DayError>>()?;
         Ok(Polymer { template, pairs })
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
@@ -1,189 +1,75 @@
+use crate::error::DayError;
+use crate::grid::Grid;
+use crate::path::{self, Path};
 use crate::Day;
+use cgmath::Point2;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
+
+/// The least risky path from the top left to the bottom right of a `width`
+/// by `height` map.
+fn safest<F>(width: usize, height: usize, risk: F) -> Path
+where
+    F: Fn(Point2<i32>) -> Option<usize>,
+{
+    let goal = Point2::new(width as i32 - 1, height as i32 - 1);
+    path::astar(Point2::new(0, 0), goal, risk).expect("The goal is always reachable")
+}
+
+/// The risk at `p` on the full map of part 2. The map repeats five times
+/// each way, with every repeat to the right or down one riskier, wrapping
+/// back around from 9 to 1.
+fn tiled_risk(input: &Grid<usize>, p: Point2<i32>) -> Option<usize> {
+    let (width, height) = (input.width(), input.height());
+    if !(0..width as i32 * 5).contains(&p.x) || !(0..height as i32 * 5).contains(&p.y) {
+        return None;
+    }
+    let tile = (p.x as usize / width) + (p.y as usize / height);
+    Some((input.wrapping(p) + tile - 1) % 9 + 1)
+}
+
+/// The map of `part` and the least risky route across it, for drawing with
+/// [`render`](crate::render). This is the only place the full map of part 2
+/// is built.
+pub fn route(input: &Grid<usize>, part: u32) -> (Grid<usize>, Path) {
+    let (width, height) = (input.width(), input.height());
+    if part == 1 {
+        let path = safest(width, height, |p| input.get(p).copied());
+        (input.clone(), path)
+    } else {
+        let path = safest(width * 5, height * 5, |p| tiled_risk(input, p));
+        let map = Grid::from_fn(width * 5, height * 5, |p| tiled_risk(input, p).unwrap());
+        (map, path)
+    }
+}
 
 pub struct Solution {}
 impl Day for Solution {
     const DAY: u32 = 15;
-    type Input = Vec<Vec<usize>>;
+    type Input = Grid<usize>;
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(mut r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines()
-            .flat_map(|l| l)
-            .map(|l| {
-                l.chars()
-                    .map(|c| c.to_digit(10).unwrap() as usize)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>())
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        // A* is only sure to find the least risky path when every step has
+        // some risk.
+        let risk = |c: char| match c.to_digit(10) {
+            Some(d @ 1..=9) => Ok(d as usize),
+            _ => Err("expected a risk from 1 to 9"),
+        };
+        Ok(Grid::from_chars(&s, risk)?)
     }
     fn p1(input: &Self::Input) -> Self::Sol1 {
-        let mut q: HashSet<(usize, usize)> = HashSet::new();
-        let mut dist: HashMap<(usize, usize), usize> = HashMap::new();
-        let mut prev: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
-        for y in 0..input.len() {
-            for x in 0..input[y].len() {
-                q.insert((x, y));
-            }
-        }
-
-        dist.insert((0, 0), 0);
-
-        while !q.is_empty() {
-            //println!("{:?}", q.len());
-            let u = q
-                .iter()
-                .filter(|k| dist.get(k).is_some())
-                .min_by_key(|k| *dist.get(k).unwrap())
-                .unwrap();
-            let u = u.clone();
-            q.remove(&u);
-
-            let offsets = &[
-                (Some(u.0), u.1.checked_sub(1)),
-                (Some(u.0), Some(u.1 + 1)),
-                (u.0.checked_sub(1), Some(u.1)),
-                (Some(u.0 + 1), Some(u.1)),
-            ];
-            for o in offsets {
-                match o {
-                    (Some(x), Some(y)) if *x < input.len() && *y < input.len() => {
-                        let v = (*x, *y);
-                        if q.contains(&v) {
-                            let alt = dist.get(&u).unwrap() + input[v.1][v.0];
-                            if dist.get(&v).is_none() || alt < dist[&v] {
-                                *dist.entry(v).or_insert(alt) = alt;
-                                *prev.entry(v).or_insert(u) = u;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        let target = (input.len() - 1, input.len() - 1);
-        let mut pos = target;
-        let mut path = vec![];
-        while let Some(prev) = prev.get(&pos) {
-            path.push(pos);
-            pos = *prev;
-        }
-
-        path.reverse();
-        println!("{:?}", path);
-        // println!("{:?}", dist);
-        // path.iter().map(|v| dist.get(v).unwrap()).sum()
-        *dist.get(&target).unwrap()
+        safest(input.width(), input.height(), |p| input.get(p).copied()).cost
     }
-    fn p2(orig_input: &Self::Input) -> Self::Sol2 {
-        let mut input = orig_input.clone();
-
-        for i in 1..5 {
-            input
-                .iter_mut()
-                .zip(orig_input.iter())
-                .for_each(|(v1, v2)| {
-                    v1.extend(v2.iter().map(|r| {
-                        let new = r + i;
-                        if new > 9 {
-                            (new - 10) % 9 + 1
-                        } else {
-                            new
-                        }
-                    }))
-                });
-        }
-
-        let mut new_input = input.clone();
-        for i in 1..5 {
-            let mut input = input.clone();
-            input.iter_mut().for_each(|v1| {
-                v1.iter_mut().for_each(|r| {
-                    let new = *r + i;
-                    *r = if new > 9 { (new - 10) % 9 + 1 } else { new }
-                })
-            });
-            new_input.extend(input.into_iter())
-        }
-
-        //     for y in 0..new_input.len() {
-        //     for x in 0..new_input.len() {
-        //         print!("{:?}", new_input[y][x]);
-        //     }
-        //     println!();
-        // }
-
-        let input = new_input;
-
-        let mut q: HashSet<(usize, usize)> = HashSet::new();
-        let mut dist: HashMap<(usize, usize), usize> = HashMap::new();
-        let mut prev: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
-        for y in 0..input.len() {
-            for x in 0..input[y].len() {
-                q.insert((x, y));
-            }
-        }
-
-        dist.insert((0, 0), 0);
-
-        let target = (input.len() - 1, input.len() - 1);
-
-        while !q.is_empty() {
-            // This is the slow operation, it should be a min heap or priority queue.
-            let u = q
-                .iter()
-                .filter(|k| dist.get(k).is_some())
-                .min_by_key(|k| (*dist.get(k).unwrap(), (target.0 - k.0 + target.1 - k.1)));
-            if u.is_none() {
-                break;
-            };
-            let u = u.unwrap();
-            let u = u.clone();
-
-            q.remove(&u);
-
-            let offsets = &[
-                (Some(u.0), u.1.checked_sub(1)),
-                (Some(u.0), Some(u.1 + 1)),
-                (u.0.checked_sub(1), Some(u.1)),
-                (Some(u.0 + 1), Some(u.1)),
-            ];
-            for o in offsets {
-                match o {
-                    (Some(x), Some(y)) if *x < input.len() && *y < input.len() => {
-                        let v = (*x, *y);
-                        if q.contains(&v) {
-                            let alt = dist.get(&u).unwrap() + input[v.1][v.0];
-                            if dist.get(&v).is_none() || alt < dist[&v] {
-                                *dist.entry(v).or_insert(alt) = alt;
-                                *prev.entry(v).or_insert(u) = u;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        let mut pos = target;
-        let mut path = vec![];
-        while let Some(prev) = prev.get(&pos) {
-            path.push(pos);
-            pos = *prev;
-        }
-
-        //path.reverse();
-        //println!("{:?}", path);
-        // println!("{:?}", dist);
-        // path.iter().map(|v| dist.get(v).unwrap()).sum()
-        *dist.get(&target).unwrap()
+    fn p2(input: &Self::Input) -> Self::Sol2 {
+        let (width, height) = (input.width() * 5, input.height() * 5);
+        safest(width, height, |p| tiled_risk(input, p)).cost
     }
 }
 
@@ -205,6 +91,20 @@ mod test {
         let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
         assert_eq!(Solution::p1(&input), 40);
         assert_eq!(Solution::p2(&input), 315);
+
+        let (map, path) = route(&input, 2);
+        assert_eq!((map.width(), map.height()), (50, 50));
+        assert_eq!(map[Point2::new(49, 49)], 9);
+        assert_eq!(path.cost, 315);
+        assert_eq!(path.points.last(), Some(&Point2::new(49, 49)));
+
+        // A* would find a path of risk 13 across this, rather than 10.
+        let input = "0199099\n0101099\n0911010\n0999901\n9009099\n0090090\n0010000\n0900000";
+        let err = Solution::process_input(input.as_bytes()).err();
+        assert!(
+            matches!(err, Some(DayError::Parse(e)) if (e.line, e.column) == (0, 0)),
+            "zero risk should be rejected"
+        );
         //unimplemented!()
     }
 }
@@ -1,43 +1,311 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
+use num::traits::{CheckedAdd, CheckedMul};
+use num::BigUint;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
-enum PacketRead {
-    Length(usize),
-    Count(usize),
+/// A BITS transmission packet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    pub version: usize,
+    pub typ: usize,
+    pub data: PacketData,
+    /// Where the packet starts, in bits from the start of the transmission
+    /// it was decoded from. Ignored when encoding.
+    pub offset: usize,
 }
 
-#[derive(Debug, PartialEq)]
-struct Packet {
-    version: usize,
-    typ: usize,
-    data: PacketData,
-}
-
-#[derive(Debug, PartialEq)]
-enum PacketData {
+#[derive(Debug, Clone, PartialEq)]
+pub enum PacketData {
     SubPackets(Vec<Packet>),
     Literal(usize),
+    /// A literal too wide for a `usize`, which only lenient decoding keeps.
+    WideLiteral(BigUint),
 }
 
-impl PacketData {
-    fn subs(&self) -> &[Packet] {
-        match self {
-            Self::SubPackets(v) => &v,
-            _ => panic!(),
+/// The numbers packets can be evaluated with.
+trait Number: Clone + Ord + From<usize> + CheckedAdd + CheckedMul {
+    /// `None` if `n` doesn't fit.
+    fn from_big(n: &BigUint) -> Option<Self>;
+}
+
+impl Number for usize {
+    fn from_big(n: &BigUint) -> Option<usize> {
+        n.try_into().ok()
+    }
+}
+
+impl Number for BigUint {
+    fn from_big(n: &BigUint) -> Option<BigUint> {
+        Some(n.clone())
+    }
+}
+
+/// How [`Packet::expression`] writes operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// Functions and comparisons, `sum(min(15, 3), 7 > 2)`.
+    Infix,
+    /// `(sum (min 15 3) (> 7 2))`
+    SExpr,
+}
+
+impl std::str::FromStr for Notation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "infix" => Ok(Notation::Infix),
+            "sexp" => Ok(Notation::SExpr),
+            _ => Err(format!("Unknown notation {:?}", s)),
         }
     }
-    fn lit(&self) -> usize {
+}
+
+/// How closely a transmission is checked while decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Accept anything that decodes to a packet: whatever follows the
+    /// outermost packet is ignored, and literals may be any width.
+    Lenient,
+    /// Also reject set bits in the padding after the outermost packet, and
+    /// literals with more than the 16 groups needed for 64 bits.
+    Strict,
+}
+
+/// Why a transmission couldn't be decoded or evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitsErrorKind {
+    NotHex,
+    /// A field or the sub-packets of an operator needed more bits than were
+    /// left.
+    Truncated {
+        needed: usize,
+        left: usize,
+    },
+    /// A literal of more than 16 groups, when decoding strictly.
+    LiteralTooWide,
+    /// Packets nested more than [`MAX_DEPTH`] deep.
+    TooDeep,
+    TrailingBits,
+    UnknownType(usize),
+    /// A literal in anything but a type 4 packet, or sub-packets in one.
+    WrongData {
+        typ: usize,
+    },
+    /// A sum, product or literal too big for a `usize`.
+    Overflow {
+        typ: usize,
+    },
+    /// An operator with a number of sub-packets it can't be evaluated with.
+    Operands {
+        typ: usize,
+        count: usize,
+    },
+}
+
+/// A problem with a transmission, and where in it the problem is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitsError {
+    /// In bits from the start of the transmission.
+    pub offset: usize,
+    /// The index of each sub-packet on the way down from the outermost
+    /// packet to the one at fault, empty for the outermost packet itself.
+    pub path: Vec<usize>,
+    pub kind: BitsErrorKind,
+}
+
+impl std::fmt::Display for BitsErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Literal(v) => *v,
-            _ => panic!(),
+            BitsErrorKind::NotHex => write!(f, "expected a hex digit"),
+            BitsErrorKind::Truncated { needed, left } => {
+                write!(f, "needs {} bits but only {} are left", needed, left)
+            }
+            BitsErrorKind::LiteralTooWide => write!(f, "literal is wider than 64 bits"),
+            BitsErrorKind::TooDeep => write!(f, "packets nest more than {} deep", MAX_DEPTH),
+            BitsErrorKind::TrailingBits => write!(f, "padding after the packet isn't zero"),
+            BitsErrorKind::UnknownType(typ) => write!(f, "unknown packet type {}", typ),
+            BitsErrorKind::WrongData { typ: 4 } => {
+                write!(f, "type 4 packets hold a literal, not sub-packets")
+            }
+            BitsErrorKind::WrongData { typ } => {
+                write!(f, "type {} packets hold sub-packets, not a literal", typ)
+            }
+            BitsErrorKind::Overflow { typ } => {
+                let op = match typ {
+                    0 => "sum",
+                    1 => "product",
+                    _ => "literal",
+                };
+                write!(f, "{} doesn't fit in {} bits", op, usize::BITS)
+            }
+            BitsErrorKind::Operands { typ, count } => {
+                write!(f, "type {} packets can't take {} sub-packets", typ, count)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BitsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at bit {}, in ", self.kind, self.offset)?;
+        if self.path.is_empty() {
+            write!(f, "the outermost packet")
+        } else {
+            let join = |path: &[usize]| {
+                let path = path.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+                path.join(".")
+            };
+            // Deep paths are cut down to their ends.
+            match self.path.len() {
+                0..=8 => write!(f, "sub-packet {}", join(&self.path)),
+                n => write!(
+                    f,
+                    "sub-packet {}...{}, {} deep",
+                    join(&self.path[..4]),
+                    join(&self.path[n - 4..]),
+                    n
+                ),
+            }
         }
     }
 }
 
+impl std::error::Error for BitsError {}
+
+/// How an operator packet says where its sub-packets end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthType {
+    /// The total length of the sub-packets in bits, in a 15 bit field.
+    Bits,
+    /// The number of sub-packets, in an 11 bit field.
+    Count,
+}
+
+/// Bits written most significant first, packed into bytes.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Write the low `width` bits of `value`.
+    fn push(&mut self, value: usize, mut width: usize) {
+        while width > 0 {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let free = 8 - self.len % 8;
+            let n = free.min(width);
+            let bits = value >> (width - n) & ((1 << n) - 1);
+            *self.bytes.last_mut().unwrap() |= (bits << (free - n)) as u8;
+            self.len += n;
+            width -= n;
+        }
+    }
+
+    fn append(&mut self, other: &BitWriter) {
+        let whole = other.len / 8;
+        other.bytes[..whole]
+            .iter()
+            .for_each(|b| self.push(*b as usize, 8));
+        let rest = other.len % 8;
+        if rest > 0 {
+            self.push((other.bytes[whole] >> (8 - rest)) as usize, rest);
+        }
+    }
+
+    /// The bits as hex, padded with zeros to a whole number of bytes.
+    fn to_hex(&self) -> String {
+        self.bytes.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+}
+
 impl Packet {
-    fn version_sum(&self) -> usize {
+    /// Decode the outermost packet of a hex encoded transmission.
+    pub fn from_hex(hex: &str, mode: Mode) -> Result<Packet, BitsError> {
+        Packet::decode(&hex_bytes(hex)?, mode)
+    }
+
+    /// Decode the outermost packet of a transmission.
+    pub fn decode(bytes: &[u8], mode: Mode) -> Result<Packet, BitsError> {
+        let mut bits = BitReader::new(bytes);
+        let mut decoder = Decoder { mode, path: vec![] };
+        let packet = decoder.packet(&mut bits)?;
+        if mode == Mode::Strict {
+            while !bits.is_empty() {
+                let offset = bits.pos;
+                if bits.read(1) == Some(1) {
+                    return Err(decoder.error(offset, BitsErrorKind::TrailingBits));
+                }
+            }
+        }
+        Ok(packet)
+    }
+
+    /// Encode as a hex transmission, the inverse of [`Packet::from_hex`].
+    /// Operator packets use `length_type` unless their sub-packets don't fit
+    /// in its field. Panics if the packet has no valid encoding, e.g. if the
+    /// version doesn't fit in 3 bits.
+    pub fn to_hex(&self, length_type: LengthType) -> String {
+        let mut w = BitWriter::default();
+        self.encode(&mut w, length_type);
+        w.to_hex()
+    }
+
+    fn encode(&self, w: &mut BitWriter, length_type: LengthType) {
+        assert!(
+            self.version < 8,
+            "version {} needs more than 3 bits",
+            self.version
+        );
+        assert!(self.typ < 8, "type {} needs more than 3 bits", self.typ);
+        w.push(self.version, 3);
+        w.push(self.typ, 3);
+        match &self.data {
+            PacketData::Literal(v) => {
+                assert_eq!(self.typ, 4, "only type 4 packets hold a literal");
+                // At least one group, even for zero.
+                let groups = (usize::BITS - v.leading_zeros()).max(1).div_ceil(4) as usize;
+                for g in (0..groups).rev() {
+                    w.push((g > 0) as usize, 1);
+                    w.push(v >> (4 * g) & 0xf, 4);
+                }
+            }
+            PacketData::WideLiteral(v) => {
+                assert_eq!(self.typ, 4, "only type 4 packets hold a literal");
+                let groups = v.to_radix_be(16);
+                for (i, g) in groups.iter().enumerate() {
+                    w.push((i + 1 < groups.len()) as usize, 1);
+                    w.push(*g as usize, 4);
+                }
+            }
+            PacketData::SubPackets(subs) => {
+                assert_ne!(self.typ, 4, "type 4 packets hold a literal");
+                let mut body = BitWriter::default();
+                subs.iter().for_each(|s| s.encode(&mut body, length_type));
+
+                let fits_bits = body.len < 1 << 15;
+                let fits_count = subs.len() < 1 << 11;
+                match (length_type, fits_bits, fits_count) {
+                    (LengthType::Bits, true, _) | (LengthType::Count, true, false) => {
+                        w.push(0, 1);
+                        w.push(body.len, 15);
+                    }
+                    (_, _, true) => {
+                        w.push(1, 1);
+                        w.push(subs.len(), 11);
+                    }
+                    _ => panic!("{} sub-packets are too many to encode", subs.len()),
+                }
+                w.append(&body);
+            }
+        }
+    }
+
+    pub fn version_sum(&self) -> usize {
         self.version
             + match &self.data {
                 PacketData::SubPackets(v) => v.iter().map(|sub| sub.version_sum()).sum(),
@@ -45,158 +313,377 @@ impl Packet {
             }
     }
 
-    fn value(&self) -> usize {
-        match self.typ {
-            0 => self.data.subs().iter().map(|s| s.value()).sum::<usize>(),
-            1 => self
-                .data
-                .subs()
+    /// Evaluate the expression the packet encodes, failing on any sum or
+    /// product that doesn't fit in a `usize`.
+    pub fn value(&self) -> Result<usize, BitsError> {
+        self.evaluate(&mut vec![])
+    }
+
+    /// Evaluate the expression the packet encodes without overflowing.
+    pub fn big_value(&self) -> Result<BigUint, BitsError> {
+        self.evaluate(&mut vec![])
+    }
+
+    /// `path` leads from the outermost packet to this one.
+    fn evaluate<T: Number>(&self, path: &mut Vec<usize>) -> Result<T, BitsError> {
+        let error = |path: &Vec<usize>, kind| BitsError {
+            offset: self.offset,
+            path: path.clone(),
+            kind,
+        };
+        // Only packets built by hand can be this deep.
+        if path.len() >= MAX_DEPTH {
+            return Err(error(path, BitsErrorKind::TooDeep));
+        }
+        let values = match &self.data {
+            PacketData::Literal(v) if self.typ == 4 => return Ok(T::from(*v)),
+            PacketData::WideLiteral(v) if self.typ == 4 => {
+                let overflow = BitsErrorKind::Overflow { typ: self.typ };
+                return T::from_big(v).ok_or_else(|| error(path, overflow));
+            }
+            PacketData::SubPackets(subs) if self.typ != 4 => subs
                 .iter()
-                .map(|s| s.value())
-                .fold(1, |mut mul, v| {
-                    mul *= v;
-                    mul
-                }),
-            2 => self.data.subs().iter().map(|s| s.value()).min().unwrap(),
-            3 => self.data.subs().iter().map(|s| s.value()).max().unwrap(),
-            4 => self.data.lit(),
-            5 => {
-                if self.data.subs()[0].value() > self.data.subs()[1].value() {
-                    1
-                } else {
-                    0
+                .enumerate()
+                .map(|(i, sub)| {
+                    path.push(i);
+                    let value = sub.evaluate(path);
+                    path.pop();
+                    value
+                })
+                .collect::<Result<Vec<T>, _>>()?,
+            _ => return Err(error(path, BitsErrorKind::WrongData { typ: self.typ })),
+        };
+        let operands = BitsErrorKind::Operands {
+            typ: self.typ,
+            count: values.len(),
+        };
+        let overflow = || error(path, BitsErrorKind::Overflow { typ: self.typ });
+        match (self.typ, &values[..]) {
+            (0, _) => values
+                .iter()
+                .try_fold(T::from(0), |sum, v| sum.checked_add(v))
+                .ok_or_else(overflow),
+            (1, _) => values
+                .iter()
+                .try_fold(T::from(1), |product, v| product.checked_mul(v))
+                .ok_or_else(overflow),
+            (2, _) => values
+                .iter()
+                .min()
+                .cloned()
+                .ok_or_else(|| error(path, operands)),
+            (3, _) => values
+                .iter()
+                .max()
+                .cloned()
+                .ok_or_else(|| error(path, operands)),
+            (5, [a, b]) => Ok(T::from((a > b) as usize)),
+            (6, [a, b]) => Ok(T::from((a < b) as usize)),
+            (7, [a, b]) => Ok(T::from((a == b) as usize)),
+            (5..=7, _) => Err(error(path, operands)),
+            (typ, _) => Err(error(path, BitsErrorKind::UnknownType(typ))),
+        }
+    }
+
+    /// The expression the packet encodes, such as `sum(min(15, 3), 7 > 2)`.
+    /// With `annotate`, the version and bit offset of each packet follow
+    /// whatever stands for it, as in `15[v6 @40]` or `sum[v4 @0](..)`.
+    pub fn expression(&self, notation: Notation, annotate: bool) -> String {
+        let mut s = String::new();
+        self.write_expression(&mut s, notation, annotate, false);
+        s
+    }
+
+    /// `nested` is whether this is an operand of an infix comparison, which
+    /// another comparison needs brackets to be.
+    fn write_expression(&self, s: &mut String, notation: Notation, annotate: bool, nested: bool) {
+        let annotation = if annotate {
+            format!("[v{} @{}]", self.version, self.offset)
+        } else {
+            String::new()
+        };
+        let subs = match &self.data {
+            PacketData::Literal(v) => {
+                *s += &format!("{}{}", v, annotation);
+                return;
+            }
+            PacketData::WideLiteral(v) => {
+                *s += &format!("{}{}", v, annotation);
+                return;
+            }
+            PacketData::SubPackets(subs) => subs,
+        };
+        let unknown;
+        let (name, symbol) = match self.typ {
+            0 => ("sum", None),
+            1 => ("product", None),
+            2 => ("min", None),
+            3 => ("max", None),
+            5 => ("gt", Some(">")),
+            6 => ("lt", Some("<")),
+            7 => ("eq", Some("==")),
+            typ => {
+                unknown = format!("type{}", typ);
+                (unknown.as_str(), None)
+            }
+        };
+
+        match (notation, symbol, &subs[..]) {
+            (Notation::Infix, Some(symbol), [a, b]) => {
+                if nested {
+                    s.push('(');
+                }
+                a.write_expression(s, notation, annotate, true);
+                *s += &format!(" {}{} ", symbol, annotation);
+                b.write_expression(s, notation, annotate, true);
+                if nested {
+                    s.push(')');
                 }
             }
-            6 => {
-                if self.data.subs()[0].value() < self.data.subs()[1].value() {
-                    1
-                } else {
-                    0
+            // Including comparisons without two operands.
+            (Notation::Infix, _, _) => {
+                *s += &format!("{}{}(", name, annotation);
+                for (i, sub) in subs.iter().enumerate() {
+                    if i > 0 {
+                        *s += ", ";
+                    }
+                    sub.write_expression(s, notation, annotate, false);
                 }
+                s.push(')');
             }
-            7 => {
-                if self.data.subs()[0].value() == self.data.subs()[1].value() {
-                    1
-                } else {
-                    0
+            (Notation::SExpr, _, _) => {
+                *s += &format!("({}{}", symbol.unwrap_or(name), annotation);
+                for sub in subs {
+                    s.push(' ');
+                    sub.write_expression(s, notation, annotate, false);
                 }
+                s.push(')');
             }
-            _ => panic!("Unknown typ"),
         }
     }
 }
 
-fn parse_subpackets(mut bits: &[char], read: PacketRead) -> (&[char], Vec<Packet>) {
-    let subs = match read {
-        PacketRead::Length(_l) => {
-            let mut subs = vec![];
-            loop {
-                let (new_bits, sub) = parse_packet(bits);
-                bits = new_bits;
-                if let Some(s) = sub {
-                    subs.push(s);
+/// Reads big-endian bit fields straight out of a byte slice.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// The next bit to read.
+    pos: usize,
+    /// One past the last bit this reader may read.
+    end: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            bytes,
+            pos: 0,
+            end: bytes.len() * 8,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.end
+    }
+
+    fn left(&self) -> usize {
+        self.end - self.pos
+    }
+
+    /// Read a `width` bit field, at most 64 bits wide, or `None` if there
+    /// aren't that many bits left.
+    fn read(&mut self, mut width: usize) -> Option<usize> {
+        if width > self.left() {
+            return None;
+        }
+        let mut value = 0;
+        while width > 0 {
+            let left = 8 - self.pos % 8;
+            let n = left.min(width);
+            let bits = (self.bytes[self.pos / 8] >> (left - n)) as usize & ((1 << n) - 1);
+            value = value << n | bits;
+            self.pos += n;
+            width -= n;
+        }
+        Some(value)
+    }
+
+    /// A reader for just the next `len` bits, which this one skips over.
+    fn take(&mut self, len: usize) -> Option<BitReader<'a>> {
+        if len > self.left() {
+            return None;
+        }
+        let sub = BitReader {
+            bytes: self.bytes,
+            pos: self.pos,
+            end: self.pos + len,
+        };
+        self.pos += len;
+        Some(sub)
+    }
+}
+
+/// How deeply packets may nest, counting the outermost one. Decoding,
+/// evaluating and printing packets all recurse once per level, so any deeper
+/// and a short transmission could run them out of stack.
+pub const MAX_DEPTH: usize = 256;
+
+/// Decodes packets, keeping track of where it is for errors.
+struct Decoder {
+    mode: Mode,
+    /// The index of each sub-packet on the way down to the current one.
+    path: Vec<usize>,
+}
+
+impl Decoder {
+    fn error(&self, offset: usize, kind: BitsErrorKind) -> BitsError {
+        BitsError {
+            offset,
+            path: self.path.clone(),
+            kind,
+        }
+    }
+
+    fn read(&self, bits: &mut BitReader, width: usize) -> Result<usize, BitsError> {
+        let (offset, left) = (bits.pos, bits.left());
+        bits.read(width).ok_or_else(|| {
+            self.error(
+                offset,
+                BitsErrorKind::Truncated {
+                    needed: width,
+                    left,
+                },
+            )
+        })
+    }
+
+    fn packet(&mut self, bits: &mut BitReader) -> Result<Packet, BitsError> {
+        let offset = bits.pos;
+        if self.path.len() >= MAX_DEPTH {
+            return Err(self.error(offset, BitsErrorKind::TooDeep));
+        }
+        let version = self.read(bits, 3)?;
+        let typ = self.read(bits, 3)?;
+
+        let data = match typ {
+            4 => self.literal(bits, offset)?,
+            _ => {
+                /* operator packet */
+                let mut subs = vec![];
+                if self.read(bits, 1)? == 0 {
+                    let len = self.read(bits, 15)?;
+                    let (start, left) = (bits.pos, bits.left());
+                    let mut bits = bits.take(len).ok_or_else(|| {
+                        self.error(start, BitsErrorKind::Truncated { needed: len, left })
+                    })?;
+                    while !bits.is_empty() {
+                        subs.push(self.sub_packet(subs.len(), &mut bits)?);
+                    }
                 } else {
-                    break;
+                    let count = self.read(bits, 11)?;
+                    for i in 0..count {
+                        subs.push(self.sub_packet(i, bits)?);
+                    }
                 }
+                PacketData::SubPackets(subs)
             }
-            subs
-        }
-        PacketRead::Count(c) => (0..c)
-            .map(|_| {
-                let sub = parse_packet(bits);
-                bits = sub.0;
-                sub.1.unwrap()
-            })
-            .collect(),
-    };
-
-    (bits, subs)
-}
-
-fn parse_packet(mut bits: &[char]) -> (&[char], Option<Packet>) {
-    if bits.is_empty() {
-        return (bits, None);
-    }
-    let version = usize::from_str_radix(&bits[..3].iter().collect::<String>(), 2).unwrap();
-    bits = &bits[3..];
-    let typ = usize::from_str_radix(&bits[..3].iter().collect::<String>(), 2).unwrap();
-    bits = &bits[3..];
-
-    //println!("{:?} {:?}", version, typ);
-    let data = match typ {
-        4 => {
-            let mut val: Vec<char> = vec![];
-            loop {
-                let c = bits[0];
-                bits = &bits[1..];
-                val.extend(&bits[..4]);
-                bits = &bits[4..];
-                if c == '0' {
-                    break;
-                }
+        };
+        Ok(Packet {
+            version,
+            typ,
+            data,
+            offset,
+        })
+    }
+
+    fn sub_packet(&mut self, i: usize, bits: &mut BitReader) -> Result<Packet, BitsError> {
+        self.path.push(i);
+        let packet = self.packet(bits)?;
+        self.path.pop();
+        Ok(packet)
+    }
+
+    /// The groups of a literal in the packet starting at `offset`, only
+    /// wide if it doesn't fit in a `usize`.
+    fn literal(&self, bits: &mut BitReader, offset: usize) -> Result<PacketData, BitsError> {
+        let most = usize::BITS as usize / 4;
+        let mut value = 0usize;
+        // Every group from the first that isn't zero, once there are too
+        // many of them for `value`.
+        let mut wide = vec![];
+        for count in 1.. {
+            let more = self.read(bits, 1)?;
+            if self.mode == Mode::Strict && count > most {
+                return Err(self.error(offset, BitsErrorKind::LiteralTooWide));
             }
-            PacketData::Literal(usize::from_str_radix(&val.iter().collect::<String>(), 2).unwrap())
-        }
-        _ => {
-            /* operator packet */
-            let length_type_id = bits[0];
-            bits = &bits[1..];
-            let sub = if length_type_id == '0' {
-                let length =
-                    usize::from_str_radix(&bits[..15].iter().collect::<String>(), 2).unwrap();
-                bits = &bits[15..];
-                let sub = parse_subpackets(&bits[..length], PacketRead::Length(length));
-                bits = &bits[length..];
-                sub.1
+            let group = self.read(bits, 4)?;
+            if !wide.is_empty() {
+                wide.push(group as u8);
+            } else if value.leading_zeros() < 4 {
+                wide = (0..most)
+                    .rev()
+                    .map(|g| (value >> (4 * g) & 0xf) as u8)
+                    .collect();
+                wide.push(group as u8);
             } else {
-                let subpackets =
-                    usize::from_str_radix(&bits[..11].iter().collect::<String>(), 2).unwrap();
-                bits = &bits[11..];
-                let (new_bits, subs) = parse_subpackets(bits, PacketRead::Count(subpackets));
-                bits = new_bits;
-                subs
-            };
-            PacketData::SubPackets(sub)
+                value = value << 4 | group;
+            }
+            if more == 0 {
+                break;
+            }
         }
-    };
-    (bits, Some(Packet { version, data, typ }))
+        Ok(if wide.is_empty() {
+            PacketData::Literal(value)
+        } else {
+            PacketData::WideLiteral(BigUint::from_radix_be(&wide, 16).unwrap())
+        })
+    }
 }
 
-fn get_bits(v: &str) -> Vec<char> {
-    v.chars()
-        .flat_map(|c| {
-            format!("{:04b}", c.to_digit(16).unwrap())
-                .chars()
-                .collect::<Vec<_>>()
-                .into_iter()
+/// The bytes of a transmission, padded with a zero if it has an odd number
+/// of hex digits.
+fn hex_bytes(hex: &str) -> Result<Vec<u8>, BitsError> {
+    let digits = hex
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            c.to_digit(16).map(|d| d as u8).ok_or(BitsError {
+                offset: i * 4,
+                path: vec![],
+                kind: BitsErrorKind::NotHex,
+            })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0))
+        .collect())
 }
 
 pub struct Solution {}
 impl Day for Solution {
     const DAY: u32 = 16;
-    type Input = String;
+    type Input = Packet;
     type Sol1 = usize;
-    type Sol2 = usize;
+    type Sol2 = BigUint;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines().next().unwrap().unwrap().to_string())
+        let l = r.lines().next().unwrap_or_else(|| Ok(String::new()))?;
+        // Four bits to a hex digit.
+        let error = |e: BitsError| ParseError::new(0, e.offset / 4, e.to_string());
+        let packet = Packet::from_hex(&l, Mode::Lenient).map_err(error)?;
+        // Anything that can't be evaluated, such as a comparison of three
+        // packets, is as broken as a transmission that can't be decoded.
+        packet.big_value().map_err(error)?;
+        Ok(packet)
     }
-    fn p1(v: &Self::Input) -> Self::Sol1 {
-        let bits = get_bits(v);
-        let p = parse_packet(&bits).1.unwrap();
-        //println!("{:?}", p);
+    fn p1(p: &Self::Input) -> Self::Sol1 {
         p.version_sum()
     }
-    fn p2(v: &Self::Input) -> Self::Sol2 {
-        let bits = get_bits(v);
-        let p = parse_packet(&bits).1.unwrap();
-        //println!("{:?}", p);
-        p.value()
+    fn p2(p: &Self::Input) -> Self::Sol2 {
+        p.big_value().expect("checked while parsing")
     }
 }
 
@@ -207,10 +694,9 @@ mod test {
     fn test() {
         let input = "8A004A801A8002F478";
         let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
-        let bits = get_bits(&input);
         assert_eq!(
-            parse_packet(&bits).1,
-            Some(Packet {
+            input,
+            Packet {
                 version: 4,
                 typ: 2,
                 data: PacketData::SubPackets(vec![Packet {
@@ -222,14 +708,16 @@ mod test {
                         data: PacketData::SubPackets(vec![Packet {
                             version: 6,
                             typ: 4,
-                            data: PacketData::Literal(15)
-                        }])
-                    }])
-                }])
-            })
+                            data: PacketData::Literal(15),
+                            offset: 58,
+                        }]),
+                        offset: 36,
+                    }]),
+                    offset: 18,
+                }]),
+                offset: 0,
+            }
         );
-        let input = "8A004A801A8002F478";
-        let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
         assert_eq!(Solution::p1(&input), 16);
 
         let input = "620080001611562C8802118E34";
@@ -246,8 +734,407 @@ mod test {
 
         let input = "C200B40A82";
         let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
-        assert_eq!(Solution::p2(&input), 3);
+        assert_eq!(Solution::p2(&input), BigUint::from(3u8));
         //assert_eq!(Solution::p2(&input), 26984457539);
         //unimplemented!()
     }
+
+    #[test]
+    fn reader() {
+        let bytes = hex_bytes("D2FE28A").unwrap();
+        assert_eq!(bytes, [0xd2, 0xfe, 0x28, 0xa0]);
+        let mut bits = BitReader::new(&bytes);
+        assert_eq!(
+            (bits.read(3), bits.read(3), bits.read(1)),
+            (Some(6), Some(4), Some(1))
+        );
+        // Across a byte boundary.
+        assert_eq!(bits.read(4), Some(0b0111));
+        assert_eq!(bits.read(13), Some(0b1_1110_0010_1000));
+        let mut sub = bits.take(4).unwrap();
+        assert_eq!(sub.read(4), Some(0xa));
+        assert!(sub.is_empty());
+        assert_eq!(bits.read(5), None);
+        assert!(bits.take(5).is_none());
+        assert_eq!(bits.read(4), Some(0));
+        assert!(bits.is_empty());
+
+        // A deep transmission, megabytes long.
+        let mut packet = Packet {
+            version: 1,
+            typ: 4,
+            data: PacketData::Literal(1),
+            offset: 0,
+        };
+        for _ in 0..10 {
+            packet = Packet {
+                version: 2,
+                typ: 0,
+                data: PacketData::SubPackets((0..4).map(|_| packet.clone()).collect()),
+                offset: 0,
+            };
+        }
+        let hex = packet.to_hex(LengthType::Count);
+        assert!(hex.len() > 1 << 20);
+        assert_eq!(
+            Packet::from_hex(&hex, Mode::Lenient).unwrap().value(),
+            Ok(1 << 20)
+        );
+
+        // A literal in `depth` sum packets, one inside the next.
+        let nested = |depth| {
+            let mut w = BitWriter::default();
+            for _ in 0..depth {
+                // Version 0, type 0 and a count of one sub-packet.
+                w.push(0, 6);
+                w.push(1, 1);
+                w.push(1, 11);
+            }
+            w.push(4, 6);
+            w.push(7, 5);
+            hex_bytes(&w.to_hex()).unwrap()
+        };
+        let packet = Packet::decode(&nested(MAX_DEPTH - 1), Mode::Lenient).unwrap();
+        assert_eq!(packet.value(), Ok(7));
+        assert_eq!(packet.big_value(), Ok(BigUint::from(7u8)));
+        assert_eq!(packet.version_sum(), 0);
+        assert!(packet.expression(Notation::SExpr, true).ends_with(')'));
+        // Deep enough to run out of stack, if it were all decoded.
+        let err = Packet::decode(&nested(200_000), Mode::Lenient).unwrap_err();
+        assert_eq!(err.kind, BitsErrorKind::TooDeep);
+        assert_eq!(err.offset, MAX_DEPTH * 18);
+        assert_eq!(err.path, [0; MAX_DEPTH]);
+        assert_eq!(
+            err.to_string(),
+            "packets nest more than 256 deep at bit 4608, in sub-packet 0.0.0.0...0.0.0.0, 256 deep"
+        );
+    }
+
+    #[test]
+    fn encode() {
+        for (hex, length_type) in [
+            ("D2FE28", LengthType::Bits),
+            ("38006F45291200", LengthType::Bits),
+            ("EE00D40C823060", LengthType::Count),
+            ("C200B40A82", LengthType::Count),
+        ] {
+            assert_eq!(
+                Packet::from_hex(hex, Mode::Lenient)
+                    .unwrap()
+                    .to_hex(length_type),
+                hex
+            );
+        }
+
+        // Decoding any encoded packet gives it back.
+        let mut rng = XorShift(0x2021_1216);
+        for _ in 0..1000 {
+            let packet = random_packet(&mut rng, 4);
+            for length_type in [LengthType::Bits, LengthType::Count] {
+                let hex = packet.to_hex(length_type);
+                let decoded = Packet::from_hex(&hex, Mode::Lenient).map(without_offsets);
+                assert_eq!(decoded.as_ref(), Ok(&packet), "{}", hex);
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |offset, path: &[usize], kind| BitsError {
+            offset,
+            path: path.to_vec(),
+            kind,
+        };
+
+        assert_eq!(
+            Packet::from_hex("D2FX28", Mode::Lenient),
+            Err(error(12, &[], BitsErrorKind::NotHex))
+        );
+        assert_eq!(
+            Packet::from_hex("", Mode::Lenient),
+            Err(error(
+                0,
+                &[],
+                BitsErrorKind::Truncated { needed: 3, left: 0 }
+            ))
+        );
+        // The literal 2021 cut off in its last group.
+        assert_eq!(
+            Packet::from_hex("D2FE", Mode::Lenient),
+            Err(error(
+                16,
+                &[],
+                BitsErrorKind::Truncated { needed: 1, left: 0 }
+            ))
+        );
+        // The last of three sub-packets counted by an operator is missing.
+        assert_eq!(
+            Packet::from_hex("EE00D40C82", Mode::Lenient),
+            Err(error(
+                40,
+                &[2],
+                BitsErrorKind::Truncated { needed: 3, left: 0 }
+            ))
+        );
+        // An operator whose sub-packets run past the end.
+        assert_eq!(
+            Packet::from_hex("38006F452912", Mode::Lenient),
+            Err(error(
+                22,
+                &[],
+                BitsErrorKind::Truncated {
+                    needed: 27,
+                    left: 26
+                }
+            ))
+        );
+
+        // Trailing bits are only looked at in strict mode.
+        let bytes = hex_bytes("D2FE29").unwrap();
+        assert!(Packet::decode(&bytes, Mode::Lenient).is_ok());
+        assert_eq!(
+            Packet::decode(&bytes, Mode::Strict),
+            Err(error(23, &[], BitsErrorKind::TrailingBits))
+        );
+        assert!(Packet::decode(&hex_bytes("D2FE28").unwrap(), Mode::Strict).is_ok());
+
+        // A literal of 17 groups, the first of them zero.
+        let mut w = BitWriter::default();
+        w.push(4, 6);
+        w.push(0b10000, 5);
+        (0..16).for_each(|g| w.push(((g < 15) as usize) << 4 | 0x1, 5));
+        let literal = hex_bytes(&w.to_hex()).unwrap();
+        assert_eq!(
+            Packet::decode(&literal, Mode::Lenient).and_then(|p| p.value()),
+            Ok(0x1111_1111_1111_1111)
+        );
+        assert_eq!(
+            Packet::decode(&literal, Mode::Strict),
+            Err(error(0, &[], BitsErrorKind::LiteralTooWide))
+        );
+        // 17 groups that are all needed.
+        let mut w = BitWriter::default();
+        w.push(4, 6);
+        (0..17).for_each(|g| w.push(((g < 16) as usize) << 4 | 0xf, 5));
+        let hex = w.to_hex();
+        let packet = Packet::from_hex(&hex, Mode::Lenient).unwrap();
+        let wide = (BigUint::from(1u8) << 68u32) - 1u8;
+        assert_eq!(packet.data, PacketData::WideLiteral(wide.clone()));
+        assert_eq!(packet.big_value(), Ok(wide));
+        assert_eq!(
+            packet.value(),
+            Err(error(0, &[], BitsErrorKind::Overflow { typ: 4 }))
+        );
+        assert_eq!(packet.to_hex(LengthType::Bits), hex);
+        assert_eq!(
+            Packet::from_hex(&hex, Mode::Strict),
+            Err(error(0, &[], BitsErrorKind::LiteralTooWide))
+        );
+
+        let literal = |v, offset| Packet {
+            version: 0,
+            typ: 4,
+            data: PacketData::Literal(v),
+            offset,
+        };
+        let operator = |typ, subs, offset| Packet {
+            version: 0,
+            typ,
+            data: PacketData::SubPackets(subs),
+            offset,
+        };
+        let comparison = operator(5, vec![literal(1, 40), literal(2, 51), literal(3, 62)], 18);
+        let sum = operator(0, vec![literal(1, 18), comparison], 0);
+        assert_eq!(
+            sum.value(),
+            Err(error(
+                18,
+                &[1],
+                BitsErrorKind::Operands { typ: 5, count: 3 }
+            ))
+        );
+        assert_eq!(
+            sum.value().unwrap_err().to_string(),
+            "type 5 packets can't take 3 sub-packets at bit 18, in sub-packet 1"
+        );
+        // Which makes for a broken input.
+        let hex = sum.to_hex(LengthType::Count);
+        let err = match Solution::process_input(hex.as_bytes()) {
+            Err(DayError::Parse(e)) => e.msg,
+            _ => panic!("{} should not parse", hex),
+        };
+        assert!(
+            err.starts_with("type 5 packets can't take 3 sub-packets"),
+            "{}",
+            err
+        );
+        // 2^40 squared needs 81 bits.
+        let square = operator(1, vec![literal(1 << 40, 40), literal(1 << 40, 91)], 18);
+        let sum = operator(0, vec![literal(1, 0), square], 0);
+        assert_eq!(
+            sum.value(),
+            Err(error(18, &[1], BitsErrorKind::Overflow { typ: 1 }))
+        );
+        assert_eq!(
+            sum.value().unwrap_err().to_string(),
+            "product doesn't fit in 64 bits at bit 18, in sub-packet 1"
+        );
+        assert_eq!(
+            sum.big_value(),
+            Ok(BigUint::from(1u8) << 80 | BigUint::from(1u8))
+        );
+        assert_eq!(
+            Solution::p2(&sum),
+            BigUint::from(1u8) << 80 | BigUint::from(1u8)
+        );
+        let sum = operator(0, vec![literal(usize::MAX, 22), literal(1, 0)], 0);
+        assert_eq!(
+            sum.value(),
+            Err(error(0, &[], BitsErrorKind::Overflow { typ: 0 }))
+        );
+        assert_eq!(sum.big_value(), Ok(BigUint::from(usize::MAX) + 1u8));
+        assert_eq!(
+            operator(3, vec![], 0).big_value(),
+            Err(error(0, &[], BitsErrorKind::Operands { typ: 3, count: 0 }))
+        );
+
+        assert_eq!(
+            operator(3, vec![], 0).value(),
+            Err(error(0, &[], BitsErrorKind::Operands { typ: 3, count: 0 }))
+        );
+        assert_eq!(
+            operator(9, vec![literal(1, 22)], 0).value(),
+            Err(error(0, &[], BitsErrorKind::UnknownType(9)))
+        );
+        assert_eq!(
+            operator(0, vec![operator(4, vec![], 22)], 0).value(),
+            Err(error(22, &[0], BitsErrorKind::WrongData { typ: 4 }))
+        );
+    }
+
+    #[test]
+    fn expression() {
+        let literal = |v| Packet {
+            version: 1,
+            typ: 4,
+            data: PacketData::Literal(v),
+            offset: 0,
+        };
+        let operator = |typ, subs| Packet {
+            version: 2,
+            typ,
+            data: PacketData::SubPackets(subs),
+            offset: 0,
+        };
+        let packet = operator(
+            0,
+            vec![
+                operator(2, vec![literal(15), literal(3)]),
+                operator(5, vec![literal(7), literal(2)]),
+            ],
+        );
+        let packet = Packet::from_hex(&packet.to_hex(LengthType::Count), Mode::Lenient).unwrap();
+        assert_eq!(
+            packet.expression(Notation::Infix, false),
+            "sum(min(15, 3), 7 > 2)"
+        );
+        assert_eq!(
+            packet.expression(Notation::SExpr, false),
+            "(sum (min 15 3) (> 7 2))"
+        );
+        assert_eq!(
+            packet.expression(Notation::Infix, true),
+            "sum[v2 @0](min[v2 @18](15[v1 @36], 3[v1 @47]), 7[v1 @76] >[v2 @58] 2[v1 @87])"
+        );
+        assert_eq!(
+            packet.expression(Notation::SExpr, true),
+            "(sum[v2 @0] (min[v2 @18] 15[v1 @36] 3[v1 @47]) (>[v2 @58] 7[v1 @76] 2[v1 @87]))"
+        );
+
+        let packet = Packet::from_hex("9C0141080250320F1802104A08", Mode::Lenient).unwrap();
+        assert_eq!(
+            packet.expression(Notation::Infix, false),
+            "sum(1, 3) == product(2, 2)"
+        );
+
+        // Comparisons of comparisons need brackets, and so do those that
+        // can't be written infix.
+        let packet = operator(
+            7,
+            vec![
+                operator(6, vec![literal(1), literal(2)]),
+                operator(6, vec![literal(1)]),
+            ],
+        );
+        assert_eq!(
+            packet.expression(Notation::Infix, false),
+            "(1 < 2) == lt(1)"
+        );
+        assert_eq!(
+            packet.expression(Notation::SExpr, false),
+            "(== (< 1 2) (< 1))"
+        );
+        assert_eq!(
+            operator(9, vec![]).expression(Notation::Infix, false),
+            "type9()"
+        );
+    }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> usize {
+            (self.next() % n) as usize
+        }
+    }
+
+    fn random_packet(rng: &mut XorShift, depth: usize) -> Packet {
+        let version = rng.below(8);
+        if depth == 0 || rng.below(3) == 0 {
+            // Literals of every width, from one group up to all 64 bits,
+            // and now and then wider.
+            let value = rng.next() as usize >> rng.below(64);
+            let data = match rng.below(8) {
+                0 => PacketData::WideLiteral(
+                    BigUint::from(rng.next() | 1) << 64 | BigUint::from(value),
+                ),
+                _ => PacketData::Literal(value),
+            };
+            return Packet {
+                version,
+                typ: 4,
+                data,
+                offset: 0,
+            };
+        }
+        let typ = [0, 1, 2, 3, 5, 6, 7][rng.below(7)];
+        let subs = (0..1 + rng.below(4))
+            .map(|_| random_packet(rng, depth - 1))
+            .collect();
+        Packet {
+            version,
+            typ,
+            data: PacketData::SubPackets(subs),
+            offset: 0,
+        }
+    }
+
+    /// Offsets zeroed, as in packets that were never decoded.
+    fn without_offsets(mut packet: Packet) -> Packet {
+        packet.offset = 0;
+        if let PacketData::SubPackets(subs) = &mut packet.data {
+            *subs = std::mem::take(subs)
+                .into_iter()
+                .map(without_offsets)
+                .collect();
+        }
+        packet
+    }
 }
@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 use std::ops::RangeInclusive;
 
 use regex::Regex;
@@ -13,20 +13,23 @@ impl Day for Solution {
     type Sol1 = i32;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         let re = Regex::new(r"(-?[0-9]+)\.\.(-?[0-9]+)").unwrap();
 
-        let l = r.lines().flatten().next().unwrap();
+        let l = r.lines().next().unwrap_or_else(|| Ok(String::new()))?;
 
         let m = re.captures_iter(&l).collect::<Vec<_>>();
+        if m.len() != 2 {
+            let err = "expected target area: x=A..B, y=C..D";
+            return Err(ParseError::new(0, 0, err).into());
+        }
+        let n =
+            |c: &regex::Captures, i| ParseError::parse::<i32>(0, &l, c.get(i).unwrap().as_str());
 
-        Ok((
-            m[0][1].parse::<i32>().unwrap()..=m[0][2].parse::<i32>().unwrap(),
-            m[1][1].parse::<i32>().unwrap()..=m[1][2].parse::<i32>().unwrap(),
-        ))
+        Ok((n(&m[0], 1)?..=n(&m[0], 2)?, n(&m[1], 1)?..=n(&m[1], 2)?))
     }
     fn p1(input: &Self::Input) -> Self::Sol1 {
         let mut max = 0;
@@ -1,8 +1,8 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 use itertools::Itertools;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 use std::ops::Add;
 
 #[derive(Clone, PartialEq)]
@@ -181,7 +181,7 @@ impl Iterator for SnailFishReduce {
     }
 }
 impl SnailFish {
-    fn magnitude(&self) -> usize {
+    pub fn magnitude(&self) -> usize {
         match self {
             SnailFish(a, b) => 3 * a.magnitude() + 2 * b.magnitude(),
         }
@@ -192,27 +192,24 @@ impl SnailFish {
 }
 
 impl std::str::FromStr for SnailFish {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut stack = vec![];
-        for c in s.chars() {
+        for (i, c) in s.chars().enumerate() {
             match c {
                 '[' | ',' => {}
                 '0'..='9' => stack.push(SnailFishVal::Val(c.to_digit(10).unwrap())),
-                ']' => {
-                    let r = stack.pop().unwrap();
-                    let l = stack.pop().unwrap();
-                    stack.push(SnailFishVal::Pair(Box::new(SnailFish(l, r))))
-                }
-                _ => panic!(),
+                ']' => match (stack.pop(), stack.pop()) {
+                    (Some(r), Some(l)) => stack.push(SnailFishVal::Pair(Box::new(SnailFish(l, r)))),
+                    _ => return Err(ParseError::new(0, i, "pair is missing a value")),
+                },
+                _ => return Err(ParseError::new(0, i, "unexpected character")),
             }
         }
-        assert_eq!(stack.len(), 1);
-        let top = stack.pop().unwrap();
 
-        match top {
-            SnailFishVal::Pair(sn) => Ok(*sn),
-            _ => panic!(),
+        match (stack.pop(), stack.is_empty()) {
+            (Some(SnailFishVal::Pair(sn)), true) => Ok(*sn),
+            _ => Err(ParseError::new(0, 0, "expected a single pair")),
         }
     }
 }
@@ -224,14 +221,14 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines()
-            .flatten()
-            .map(|l| l.parse::<SnailFish>().unwrap())
-            .collect())
+        r.lines()
+            .enumerate()
+            .map(|(i, l)| Ok(l?.parse::<SnailFish>().map_err(|e| e.offset(i, 0))?))
+            .collect()
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         let r: SnailFish = v.iter().cloned().sum();
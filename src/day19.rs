@@ -1,9 +1,9 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 use cgmath::{Point3, Vector3};
 use rayon::prelude::*;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 #[derive(Debug, Clone)]
 pub struct Scanner {
@@ -13,6 +13,14 @@ pub struct Scanner {
 }
 
 impl Scanner {
+    /// Where the scanner is relative to the first one placed by [`find_map`].
+    pub fn offset(&self) -> Vector3<i32> {
+        self.offset
+    }
+    /// The beacons this scanner can see, sorted.
+    pub fn beacons(&self) -> &[Point3<i32>] {
+        &self.pts
+    }
     fn permutes(&self) -> Vec<Vec<Point3<i32>>> {
         let all = self
             .pts
@@ -61,7 +69,8 @@ impl Scanner {
     }
 }
 
-fn find_map(scanners: &[Scanner]) -> Vec<Scanner> {
+/// Orient and place every scanner relative to one another.
+pub fn find_map(scanners: &[Scanner]) -> Vec<Scanner> {
     let mut scanners = scanners.to_vec();
     let mut map = vec![scanners.pop().unwrap()];
     use std::sync::RwLock;
@@ -118,7 +127,7 @@ fn find_map(scanners: &[Scanner]) -> Vec<Scanner> {
 
         if let Some(f) = found {
             scanners.retain(|x| x.idx != f.idx);
-            println!("{:?}", f);
+            log::debug!("{:?}", f);
             map.push(f);
             if scanners.is_empty() {
                 break 'map_done;
@@ -134,38 +143,39 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines();
+        let mut lines = r.lines().enumerate();
         let mut scanners = vec![];
         let mut idx = 0;
-        loop {
-            if lines.by_ref().next().is_some() {
-                let mut pts: Vec<_> = lines
-                    .by_ref()
-                    .flatten()
-                    .take_while(|l| !l.is_empty())
-                    .map(|l| {
-                        let mut p = l.split(",").map(|p| p.parse::<i32>().unwrap());
-                        Point3 {
-                            x: p.next().unwrap(),
-                            y: p.next().unwrap(),
-                            z: p.next().unwrap(),
-                        }
-                    })
-                    .collect();
-                pts.sort_by_key(|p| (p.x, p.y, p.z));
-                scanners.push(Scanner {
-                    idx,
-                    offset: Vector3::from([0, 0, 0]),
-                    pts,
-                });
-                idx += 1;
-            } else {
-                break;
+        while let Some((i, header)) = lines.next() {
+            if !header?.starts_with("--- scanner") {
+                return Err(ParseError::new(i, 0, "expected a scanner header").into());
+            }
+            let mut pts = vec![];
+            for (i, l) in lines.by_ref() {
+                let l = l?;
+                if l.is_empty() {
+                    break;
+                }
+                let p = l
+                    .split(',')
+                    .map(|p| ParseError::parse(i, &l, p))
+                    .collect::<Result<Vec<i32>, _>>()?;
+                match p[..] {
+                    [x, y, z] => pts.push(Point3 { x, y, z }),
+                    _ => return Err(ParseError::new(i, 0, "expected x,y,z").into()),
+                }
             }
+            pts.sort_by_key(|p| (p.x, p.y, p.z));
+            scanners.push(Scanner {
+                idx,
+                offset: Vector3::from([0, 0, 0]),
+                pts,
+            });
+            idx += 1;
         }
         Ok(scanners)
     }
@@ -206,7 +216,7 @@ mod test {
     #[test]
     fn test() {
         use std::io::Read;
-        let mut input = std::io::Cursor::new(std::fs::read("test/day19.txt").unwrap());
+        let mut input = std::io::Cursor::new(std::fs::read(Solution::example("example")).unwrap());
         let input = Solution::process_input(std::io::BufReader::new(&mut input)).unwrap();
         assert_eq!(Solution::p1(&input), 79);
         assert_eq!(Solution::p2(&input), 3621);
@@ -1,50 +1,51 @@
+use crate::error::{DayError, ParseError};
+use crate::grid::Grid;
 use crate::Day;
-use cgmath::Point2;
+use cgmath::{Point2, Vector2};
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
+/// An image on an infinite plane: `img` holds the pixels that have been
+/// worked out and everything beyond it is `outside`.
 #[derive(Clone)]
 pub struct Enhance {
     enhance: Vec<bool>,
-    img: HashMap<Point2<i32>, bool>,
+    img: Grid<bool>,
+    outside: bool,
 }
 
-fn points(p: Point2<i32>) -> Vec<Point2<i32>> {
-    [
-        (p.x - 1, p.y - 1),
-        (p.x, p.y - 1),
-        (p.x + 1, p.y - 1),
-        (p.x - 1, p.y),
-        (p.x, p.y),
-        (p.x + 1, p.y),
-        (p.x - 1, p.y + 1),
-        (p.x, p.y + 1),
-        (p.x + 1, p.y + 1),
-    ]
-    .into_iter()
-    .map(|(x, y)| Point2::new(x, y))
-    .collect()
-}
-fn offset(eh: &Enhance, p: Point2<i32>, s: usize) -> bool {
-    let outside = match (s % 2 == 1, eh.enhance[0]) {
-        (_, false) => false,
-        (true, true) => true,
-        (false, true) => false,
-    };
+impl Enhance {
+    fn pixel(&self, p: Point2<i32>) -> bool {
+        *self.img.get(p).unwrap_or(&self.outside)
+    }
 
-    let offset = points(p)
-        .into_iter()
-        .map(|p| eh.img.get(&p).unwrap_or(&outside))
-        .fold(0, |mut state, p| {
-            state <<= 1;
-            if *p {
-                state |= 1;
-            }
-            state
+    /// Enhance once. The image grows by a pixel on every side, and the
+    /// infinite outside is enhanced from 9 pixels that are all the same.
+    fn step(&self) -> Enhance {
+        let border = Vector2::new(1, 1);
+        let img = Grid::from_fn(self.img.width() + 2, self.img.height() + 2, |p| {
+            let offset = (-1..=1)
+                .flat_map(|y| (-1..=1).map(move |x| Vector2::new(x, y)))
+                .fold(0, |state, d| {
+                    state << 1 | self.pixel(p - border + d) as usize
+                });
+            self.enhance[offset]
         });
+        let outside = self.enhance[if self.outside { 511 } else { 0 }];
+        Enhance {
+            enhance: self.enhance.clone(),
+            img,
+            outside,
+        }
+    }
 
-    eh.enhance[offset]
+    fn lit(mut self, steps: usize) -> usize {
+        for _ in 0..steps {
+            self = self.step();
+        }
+        assert!(!self.outside, "Infinitely many pixels are lit");
+        self.img.values().filter(|x| **x).count()
+    }
 }
 
 pub struct Solution {}
@@ -54,62 +55,40 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(mut r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines().flatten();
-        let enhance = lines.next().unwrap();
-        let enhance: Vec<_> = enhance.chars().map(|x| x == '#').collect();
-
-        let img = lines
-            .skip(1)
+        let pixel = |x| match x {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err("expected # or ."),
+        };
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        let (enhance, img) = s.split_once("\n\n").unwrap_or((&s, ""));
+        let enhance = enhance
+            .chars()
             .enumerate()
-            .flat_map(move |(y, l)| {
-                l.chars()
-                    .map(|x| x == '#')
-                    .enumerate()
-                    //.filter(|(_, v)| *v)
-                    .map(move |(x, v)| (Point2::new(x as i32, y as i32), v))
-                    .collect::<Vec<_>>()
-                    .into_iter()
-            })
-            .collect();
+            .map(|(c, x)| pixel(x).map_err(|e| ParseError::new(0, c, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if enhance.len() != 512 {
+            let err = "enhancement algorithm must be 512 pixels";
+            return Err(ParseError::new(0, 0, err).into());
+        }
+        let img = Grid::from_chars(img, pixel).map_err(|e| e.offset(2, 0))?;
 
-        assert_eq!(enhance.len(), 512);
-        Ok(Enhance { enhance, img })
+        Ok(Enhance {
+            enhance,
+            img,
+            outside: false,
+        })
     }
     fn p1(eh: &Self::Input) -> Self::Sol1 {
-        let mut eh = eh.clone();
-        for s in 0..2 {
-            //assert!(eh.img.values().all(|x| *x));
-            let new: HashMap<_, _> = eh
-                .img
-                .keys()
-                .flat_map(|p| points(*p))
-                .map(|p| (p, offset(&eh, p, s)))
-                .collect();
-
-            eh.img = new;
-        }
-        //assert!(eh.img.values().all(|x| *x));
-        eh.img.values().filter(|x| **x).count()
+        eh.clone().lit(2)
     }
     fn p2(eh: &Self::Input) -> Self::Sol2 {
-        let mut eh = eh.clone();
-        for s in 0..50 {
-            //assert!(eh.img.values().all(|x| *x));
-            let new: HashMap<_, _> = eh
-                .img
-                .keys()
-                .flat_map(|p| points(*p))
-                .map(|p| (p, offset(&eh, p, s)))
-                .collect();
-
-            eh.img = new;
-        }
-        //assert!(eh.img.values().all(|x| *x));
-        eh.img.values().filter(|x| **x).count()
+        eh.clone().lit(50)
     }
 }
 
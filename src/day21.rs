@@ -1,8 +1,8 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 use itertools::Itertools;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Players {
     p1: usize,
@@ -22,30 +22,25 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut lines = r.lines().flatten();
-        let p1 = lines
-            .next()
-            .unwrap()
-            .split_whitespace()
-            .last()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-        let p2 = lines
-            .next()
-            .unwrap()
-            .split_whitespace()
-            .last()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
+        let mut lines = r.lines();
+        let mut player = |i| -> Result<usize, DayError> {
+            let l = lines.next().unwrap_or_else(|| Ok(String::new()))?;
+            let pos = l
+                .rsplit_once(": ")
+                .ok_or_else(|| ParseError::new(i, 0, "expected a starting position"))?
+                .1;
+            match ParseError::parse(i, &l, pos)? {
+                p @ 1..=10 => Ok(p - 1),
+                _ => Err(ParseError::at(i, &l, pos, "position must be between 1 and 10").into()),
+            }
+        };
         Ok(Players {
-            p1: p1 - 1,
-            p2: p2 - 1,
+            p1: player(0)?,
+            p2: player(1)?,
         })
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
@@ -1,8 +1,9 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 use std::ops::Range;
+use std::str::FromStr;
 
 #[derive(Debug, Clone)]
 pub struct Cube {
@@ -25,7 +26,10 @@ fn intersect(s: &Range<i64>, o: &Range<i64>) -> Option<Range<i64>> {
 }
 
 impl Cube {
-    fn intersect(&self, other: &Cube) -> bool {
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+    pub fn intersect(&self, other: &Cube) -> bool {
         self.x.start < other.x.end
             && self.x.end > other.x.start
             && self.y.start < other.y.end
@@ -34,7 +38,7 @@ impl Cube {
             && self.z.end > other.z.start
     }
 
-    fn volume(&self) -> usize {
+    pub fn volume(&self) -> usize {
         ((self.x.end - self.x.start) * (self.y.end - self.y.start) * (self.z.end - self.z.start))
             as usize
     }
@@ -83,7 +87,7 @@ impl Cube {
         );
         cubes
     }
-    fn intersection(&self, other: &Cube) -> Option<Cube> {
+    pub fn intersection(&self, other: &Cube) -> Option<Cube> {
         if self.intersect(other) {
             let ranges = &[
                 (&self.x, &other.x),
@@ -106,6 +110,43 @@ impl Cube {
     }
 }
 
+impl FromStr for Cube {
+    type Err = ParseError;
+    /// A reboot step such as `on x=10..12,y=10..12,z=10..12`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (on, suffix) = if let Some(suffix) = s.strip_prefix("on ") {
+            (true, suffix)
+        } else if let Some(suffix) = s.strip_prefix("off ") {
+            (false, suffix)
+        } else {
+            return Err(ParseError::new(0, 0, "expected on or off"));
+        };
+        let mut ranges = suffix
+            .split(',')
+            .zip(["x=", "y=", "z="])
+            .map(|(r, axis)| {
+                let range = r
+                    .strip_prefix(axis)
+                    .ok_or_else(|| ParseError::at(0, s, r, format!("expected {}", axis)))?;
+                let (start, end) = range
+                    .split_once("..")
+                    .ok_or_else(|| ParseError::at(0, s, range, "expected start..end"))?;
+                let range =
+                    ParseError::parse(0, s, start)?..ParseError::parse::<i64>(0, s, end)? + 1;
+                if range.is_empty() {
+                    return Err(ParseError::at(0, s, start, "range is empty"));
+                }
+                Ok(range)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        match (ranges.next(), ranges.next(), ranges.next()) {
+            (Some(x), Some(y), Some(z)) => Ok(Cube { on, x, y, z }),
+            _ => Err(ParseError::new(0, 0, "expected x, y and z ranges")),
+        }
+    }
+}
+
 pub struct Solution {}
 impl Day for Solution {
     const DAY: u32 = 22;
@@ -113,35 +154,14 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        Ok(r.lines()
-            .flatten()
-            .map(|l| {
-                let (on, suffix) = if let Some(suffix) = l.strip_prefix("on ") {
-                    (true, suffix)
-                } else {
-                    (false, l.strip_prefix("off ").unwrap())
-                };
-                let mut nums = suffix.split(',').flat_map(move |v| {
-                    v.chars()
-                        .skip(2)
-                        .collect::<String>()
-                        .split("..")
-                        .into_iter()
-                        .map(|x| x.parse::<i64>().unwrap())
-                        .collect::<Vec<_>>()
-                });
-                Cube {
-                    on,
-                    x: nums.next().unwrap()..(nums.next().unwrap() + 1),
-                    y: nums.next().unwrap()..(nums.next().unwrap() + 1),
-                    z: nums.next().unwrap()..(nums.next().unwrap() + 1),
-                }
-            })
-            .collect())
+        r.lines()
+            .enumerate()
+            .map(|(i, l)| Ok(l?.parse().map_err(|e: ParseError| e.offset(i, 0))?))
+            .collect()
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
         // // ORIGINAL PART 1 Solution
@@ -182,7 +202,7 @@ impl Day for Solution {
         let mut map: Vec<Cube> = vec![];
         let v = v.to_vec();
         for (s, c) in v.into_iter().enumerate() {
-            println!("{:?}", s);
+            log::trace!("{:?}", s);
             if map.len() == 0 {
                 map.push(c);
             } else {
@@ -210,7 +230,7 @@ impl Day for Solution {
                 }
             }
         }
-        println!("{:?}", map.len());
+        log::debug!("{:?}", map.len());
         map.iter().map(|x| x.count()).sum::<usize>()
     }
 }
@@ -307,6 +327,15 @@ on x=-53470..21291,y=-120233..-33476,z=-44150..38147\n\
 off x=-93533..-4276,y=-16170..68771,z=-104985..-24507";
         let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
         assert_eq!(Solution::p2(&input), 2758514936282235);
+
+        let cube = "on x=10..12,y=10..12,z=10..12".parse::<Cube>().unwrap();
+        assert!(cube.is_on());
+        assert_eq!(cube.volume(), 27);
+        let err = "off x=1..2,y=3..x,z=5..6".parse::<Cube>().unwrap_err();
+        assert_eq!((err.line, err.column), (0, 16));
+        let err =
+            Solution::process_input("on x=1..2,y=1..2,z=1..2\noff x=1..2,y=2..1,z=1..1".as_bytes());
+        assert!(matches!(err, Err(DayError::Parse(e)) if (e.line, e.column) == (1, 13)));
         //unimplemented!()
     }
 }
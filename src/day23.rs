@@ -1,3 +1,5 @@
+use crate::error::{DayError, ParseError};
+use crate::grid::Grid;
 use crate::Day;
 use cgmath::{Point2, Vector2};
 use itertools::Itertools;
@@ -6,7 +8,6 @@ use petgraph::graph::{NodeIndex, UnGraph};
 #[allow(unused_imports)]
 use std::collections::*;
 use std::fmt::Display;
-use std::io::Result as IoResult;
 use std::str::FromStr;
 
 #[derive(Hash, Eq, PartialEq, Debug, Copy, Clone, Ord, PartialOrd)]
@@ -17,6 +18,9 @@ pub enum Amp {
     Desert,
 }
 
+/// Every kind of amphipod, in the order of their rooms from left to right.
+const AMPS: [Amp; 4] = [Amp::Amber, Amp::Bronze, Amp::Copper, Amp::Desert];
+
 impl Amp {
     fn energy(&self) -> usize {
         match self {
@@ -29,14 +33,14 @@ impl Amp {
 }
 
 impl FromStr for Amp {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let amp = match s {
             "A" => Amp::Amber,
             "B" => Amp::Bronze,
             "C" => Amp::Copper,
             "D" => Amp::Desert,
-            _ => return Err(()),
+            _ => return Err(ParseError::new(0, 0, format!("unknown amphipod {:?}", s))),
         };
         Ok(amp)
     }
@@ -60,6 +64,8 @@ pub enum Space {
     Floor(Option<Amp>),
     Room(Option<Amp>),
     Wall,
+    /// Beyond the walls, where the map has spaces.
+    Outside,
 }
 impl std::fmt::Display for Space {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -67,6 +73,7 @@ impl std::fmt::Display for Space {
             Space::IllegalFloor | Space::Floor(None) | Space::Room(None) => ".".to_string(),
             Space::Floor(Some(a)) | Space::Room(Some(a)) => format!("{}", a),
             Space::Wall => "#".to_string(),
+            Space::Outside => " ".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -74,10 +81,7 @@ impl std::fmt::Display for Space {
 impl Space {
     fn walkable(&self) -> bool {
         // This is a path (even if it may currently be blocked)
-        match self {
-            Space::Wall => false,
-            _ => true,
-        }
+        !matches!(self, Space::Wall | Space::Outside)
     }
 
     fn stand(&self) -> bool {
@@ -91,26 +95,20 @@ impl Space {
     fn blocked(&self) -> bool {
         // Indicates that pathfinding algorithm cannot pass through this square
         match self {
-            Space::Room(Some(_)) | Space::Floor(Some(_)) | Space::Wall => true,
+            Space::Room(Some(_)) | Space::Floor(Some(_)) | Space::Wall | Space::Outside => true,
             _ => false,
         }
     }
 }
 
 impl FromStr for Space {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let space = match s {
             "#" => Space::Wall,
             "." => Space::Floor(None),
-            " " => return Err(()),
-            c => {
-                if let Ok(amp) = c.parse() {
-                    Space::Room(Some(amp))
-                } else {
-                    return Err(());
-                }
-            }
+            " " => Space::Outside,
+            c => Space::Room(Some(c.parse()?)),
         };
 
         Ok(space)
@@ -120,36 +118,70 @@ impl FromStr for Space {
 #[derive(Debug, Clone)]
 pub struct Burrow {
     rooms: HashMap<Amp, Vec<Point2<i32>>>,
-    burrow: HashMap<Point2<i32>, Space>,
+    burrow: Grid<Space>,
 }
 
 impl std::fmt::Display for Burrow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let max_x = self.burrow.keys().max_by_key(|p| p.x).unwrap().x as usize + 1;
-        let max_y = self.burrow.keys().max_by_key(|p| p.y).unwrap().y as usize + 1;
-        let mut buf = vec![" ".to_string(); max_x * max_y];
-
-        self.burrow
-            .iter()
-            .for_each(|(p, v)| buf[p.x as usize + p.y as usize * max_x] = format!("{}", v));
-
-        buf.chunks_exact(max_x).try_for_each(|x| {
-            x.iter().try_for_each(|c| write!(f, "{}", c))?;
-            writeln!(f)
-        })
+        write!(f, "{}", self.burrow)
     }
 }
 impl Burrow {
+    /// The least energy needed to sort every amphipod into its room.
+    pub fn least_energy(&self) -> usize {
+        run(self.clone())
+    }
+    /// The full burrow of part 2, which has two more rows folded up under
+    /// the first row of each room.
+    pub fn unfold(&self) -> Burrow {
+        use Amp::*;
+        self.insert_rows(&[
+            [Desert, Copper, Bronze, Amber],
+            [Desert, Bronze, Amber, Copper],
+        ])
+    }
+    /// Insert `rows` of amphipods, one for each room from left to right,
+    /// under the first row of the rooms. The rooms get deeper by one for
+    /// each row.
+    fn insert_rows(&self, rows: &[[Amp; 4]]) -> Burrow {
+        let top = self.rooms.values().map(|r| r[0].y).min().unwrap();
+        let below = Vector2::new(0, rows.len() as i32);
+        let columns = AMPS.map(|amp| self.rooms[&amp][0].x);
+
+        let (width, height) = (self.burrow.width(), self.burrow.height() + rows.len());
+        let burrow = Grid::from_fn(width, height, |p| {
+            if p.y <= top {
+                self.burrow[p].clone()
+            } else if p.y > top + below.y {
+                self.burrow[p - below].clone()
+            } else if let Some(i) = columns.iter().position(|x| *x == p.x) {
+                Space::Room(Some(rows[(p.y - top - 1) as usize][i]))
+            } else if columns.iter().any(|x| (x - p.x).abs() == 1) {
+                Space::Wall
+            } else {
+                Space::Outside
+            }
+        });
+        let rooms = AMPS
+            .iter()
+            .zip(columns)
+            .map(|(amp, x)| {
+                let depth = self.rooms[amp].len() + rows.len();
+                let room = (top..).take(depth).map(|y| Point2::new(x, y)).collect();
+                (*amp, room)
+            })
+            .collect();
+        Burrow { rooms, burrow }
+    }
     fn all_good(&self) -> bool {
         self.rooms.iter().all(|(amp, rooms)| {
-            rooms.iter().all(|r| match self.burrow.get(r).unwrap() {
-                Space::Room(Some(room_amp)) if room_amp == amp => true,
-                _ => false,
-            })
+            rooms
+                .iter()
+                .all(|r| matches!(&self.burrow[*r], Space::Room(Some(a)) if a == amp))
         })
     }
     fn move_amp(&mut self, from: Point2<i32>, to: Point2<i32>) {
-        let amp = match self.burrow.get_mut(&from).unwrap() {
+        let amp = match &mut self.burrow[from] {
             Space::Room(amp) | Space::Floor(amp) => {
                 let amp = amp.take();
                 amp.unwrap()
@@ -157,7 +189,7 @@ impl Burrow {
             _ => panic!("invalid location"),
         };
 
-        match self.burrow.get_mut(&to).unwrap() {
+        match &mut self.burrow[to] {
             Space::Room(loc) | Space::Floor(loc) if loc.is_none() => {
                 *loc = Some(amp);
             }
@@ -165,42 +197,40 @@ impl Burrow {
         }
     }
     fn can_move(&self, amp: Amp, from: Point2<i32>, to: Point2<i32>) -> bool {
-        let from_space = self.burrow.get(&from).unwrap();
+        let from_space = &self.burrow[from];
         let is_room = match from_space {
             Space::Room(_) => true,
             _ => false,
         };
 
         if is_room {
+            // Stay put once in your own room with only your own kind below.
             let your_rooms = self.rooms.get(&amp).unwrap();
-            if *your_rooms.iter().last().unwrap() == from
-                || (your_rooms.contains(&from)
-                    && your_rooms
-                        .iter()
-                        .all(|rp| match self.burrow.get(&rp).unwrap() {
-                            Space::Room(Some(a)) if *a == amp => true,
-                            _ => false,
-                        }))
+            if your_rooms.contains(&from)
+                && your_rooms
+                    .iter()
+                    .skip_while(|rp| **rp != from)
+                    .all(|rp| matches!(&self.burrow[*rp], Space::Room(Some(a)) if *a == amp))
             {
                 return false;
             }
         }
 
         self.burrow
-            .get(&to)
+            .get(to)
             .map(|b| match b {
                 Space::Room(_) => {
                     let rooms = self.rooms.get(&amp).unwrap();
                     !is_room
                         && rooms.contains(&to)
-                        && rooms.iter().all(|rp| match self.burrow.get(rp).unwrap() {
+                        && rooms.iter().all(|rp| match &self.burrow[*rp] {
                             Space::Room(a) => a.map(|a| a == amp).unwrap_or(true),
                             _ => panic!(),
                         })
                         && rooms
                             .iter()
                             .rev()
-                            .filter(|rp| match self.burrow.get(rp).unwrap() {
+                            .filter(|rp| match &self.burrow[**rp] {
                                 Space::Room(a) => a.is_none(),
                                 _ => panic!(),
                             })
@@ -215,43 +245,37 @@ impl Burrow {
     }
 }
 impl FromStr for Burrow {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut y = 0i32;
-        let mut burrow = s
+        // The lines below the hallway are often missing their trailing spaces.
+        let width = s.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let s = s
             .lines()
-            .flat_map(|l| {
-                let iter = l
-                    .chars()
-                    .enumerate()
-                    .filter(|(_, c)| *c != ' ')
-                    .map(|(x, c)| (Point2::new(x as i32, y), String::from(c).parse().unwrap()))
-                    .collect::<Vec<_>>();
-                y += 1;
-                iter.into_iter()
-            })
-            .collect::<HashMap<_, _>>();
+            .map(|l| format!("{:<width$}", l, width = width))
+            .join("\n");
+        let mut burrow =
+            Grid::from_chars(&s, |c| String::from(c).parse::<Space>().map_err(|e| e.msg))?;
 
         let illegal = burrow
             .iter()
             .flat_map(|(p, b)| match b {
                 Space::Floor(_)
-                    if matches!(burrow.get(&(p + Vector2::new(0, 1))), Some(Space::Room(_))) =>
+                    if matches!(burrow.get(p + Vector2::new(0, 1)), Some(Space::Room(_))) =>
                 {
-                    Some(*p)
+                    Some(p)
                 }
                 _ => None,
             })
             .collect::<Vec<_>>();
 
         illegal.iter().for_each(|p| {
-            burrow.insert(*p, Space::IllegalFloor);
+            burrow[*p] = Space::IllegalFloor;
         });
 
         let mut rooms = burrow
             .iter()
             .flat_map(|(p, b)| match b {
-                Space::Room(_) => Some(*p),
+                Space::Room(_) => Some(p),
                 _ => None,
             })
             .collect::<Vec<_>>();
@@ -259,45 +283,36 @@ impl FromStr for Burrow {
 
         let mut groups = rooms.iter().group_by(|p| p.x);
 
-        let mut rooms = groups
+        let groups = groups
             .into_iter()
             .map(|(_key, group)| group.cloned().collect::<Vec<_>>())
-            .zip([Amp::Amber, Amp::Bronze, Amp::Copper, Amp::Desert].into_iter())
-            .map(|(x, y)| (y, x))
-            .collect();
+            .collect::<Vec<_>>();
+        if groups.len() != 4 {
+            return Err(ParseError::new(0, 0, "expected 4 side rooms"));
+        }
+        let rooms = groups.into_iter().zip(AMPS).map(|(x, y)| (y, x)).collect();
 
         Ok(Burrow { rooms, burrow })
     }
 }
 
 fn run(v: Burrow) -> usize {
-    println!("{:?}", v);
-    println!("{}", v);
+    log::trace!("{:?}", v);
+    log::debug!("\n{}", v);
 
     let mut ungraph = UnGraph::<Point2<i32>, _>::new_undirected();
     let node_indices = v
         .burrow
         .iter()
-        .map(|(p, _b)| (*p, ungraph.add_node(*p)))
+        .map(|(p, _b)| (p, ungraph.add_node(p)))
         .collect::<HashMap<_, _>>();
 
     node_indices.iter().for_each(|(p, n1)| {
-        let b = v.burrow.get(&p).unwrap();
-        if b.walkable() {
-            let dirs = [
-                Vector2::new(1, 0),
-                Vector2::new(0, 1),
-                Vector2::new(-1, 0),
-                Vector2::new(0, -1),
-            ];
-
-            for d in dirs {
-                let p2 = p + d;
-                if let Some(b2) = v.burrow.get(&p2) {
-                    if b2.walkable() {
-                        let n2 = node_indices.get(&p2).unwrap();
-                        ungraph.add_edge(*n1, *n2, 1);
-                    }
+        if v.burrow[*p].walkable() {
+            for p2 in v.burrow.neighbours4(*p) {
+                if v.burrow[p2].walkable() {
+                    let n2 = node_indices.get(&p2).unwrap();
+                    ungraph.add_edge(*n1, *n2, 1);
                 }
             }
         }
@@ -307,7 +322,7 @@ fn run(v: Burrow) -> usize {
         .iter()
         .flat_map(|(p, ni)| {
             v.burrow
-                .get(p)
+                .get(*p)
                 .filter(|b| b.stand())
                 .map(|b| (*p, dijkstra(&ungraph, *ni, None, |_| 1)))
         })
@@ -338,7 +353,7 @@ fn run(v: Burrow) -> usize {
             .burrow
             .iter()
             .flat_map(|(p, x)| match x {
-                Space::Floor(amp) | Space::Room(amp) => amp.map(|a| (*p, a)),
+                Space::Floor(amp) | Space::Room(amp) => amp.map(|a| (p, a)),
                 _ => None,
             })
             .collect::<Vec<(Point2<_>, Amp)>>();
@@ -355,10 +370,12 @@ fn run(v: Burrow) -> usize {
                 let mut legal_endpoint: Vec<_> = burrow
                     .burrow
                     .iter()
-                    .filter(|(p, _b)| burrow.can_move(amp, amp_pos, **p))
+                    .filter(|(p, _b)| burrow.can_move(amp, amp_pos, *p))
                     .collect();
-                legal_endpoint.sort_by_key(|(p, _)| dist.get(&amp_pos).unwrap().get(&node_indices[&p]).unwrap());
-                    
+                legal_endpoint.sort_by_key(|(p, _)| {
+                    dist.get(&amp_pos).unwrap().get(&node_indices[&p]).unwrap()
+                });
+
                 let mut reachable = HashSet::new();
 
                 petgraph::visit::depth_first_search(
@@ -370,7 +387,7 @@ fn run(v: Burrow) -> usize {
                             DfsEvent::Discover(n, _) => {
                                 if graph[n] == amp_pos {
                                     Control::Continue
-                                } else if burrow.burrow[&graph[n]].blocked() {
+                                } else if burrow.burrow[graph[n]].blocked() {
                                     Control::<()>::Prune
                                 } else {
                                     reachable.insert(n);
@@ -392,7 +409,7 @@ fn run(v: Burrow) -> usize {
             })
             .scan(best, move |mut best, (amp_pos, amp, p, b)| {
                 let mut burrow = burrow.clone();
-                burrow.move_amp(amp_pos, *p);
+                burrow.move_amp(amp_pos, p);
                 let found = dfs(
                     states,
                     burrow,
@@ -410,7 +427,7 @@ fn run(v: Burrow) -> usize {
                     }
                     (Some(f), Some(b)) if *b > f => {
                         *best = found;
-                        println!("Best {:?}", best);
+                        log::debug!("Best {:?}", best);
                     }
                     _ => {}
                 }
@@ -439,22 +456,20 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(mut r: R) -> IoResult<Self::Input>
+    fn process_input<R>(mut r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         let mut s = String::new();
         r.read_to_string(&mut s)?;
 
-        Ok(s.parse().unwrap())
+        Ok(s.parse()?)
     }
     fn p1(v: &Self::Input) -> Self::Sol1 {
-        run(v.clone())
+        v.least_energy()
     }
     fn p2(v: &Self::Input) -> Self::Sol2 {
-        let input = std::fs::read_to_string("input/day23p2.txt").unwrap();
-        let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
-        run(input)
+        v.unfold().least_energy()
     }
 }
 
@@ -484,9 +499,17 @@ mod test {
             "  #A#D#C#A#  \n",
             "  #########  "
         );
+        let unfolded = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
+        let input = concat!(
+            "#############\n",
+            "#...........#\n",
+            "###B#C#B#D###\n",
+            "  #A#D#C#A#  \n",
+            "  #########  "
+        );
         let input = Solution::process_input(std::io::BufReader::new(input.as_bytes())).unwrap();
-        assert_eq!(run(input), 44169);
-        //assert_eq!(Solution::p2(&input), 26984457539);
-        //unimplemented!()
+        assert_eq!(input.unfold().to_string(), unfolded.to_string());
+        assert_eq!(input.unfold().rooms, unfolded.rooms);
+        assert_eq!(Solution::p2(&input), 44169);
     }
 }
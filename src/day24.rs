@@ -1,7 +1,7 @@
+use crate::error::{DayError, ParseError};
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -13,14 +13,14 @@ pub enum Reg {
 }
 
 impl FromStr for Reg {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let r = match s {
             "w" => Reg::W,
             "x" => Reg::X,
             "y" => Reg::Y,
             "z" => Reg::Z,
-            _ => return Err(()),
+            _ => return Err(ParseError::new(0, 0, format!("unknown register {:?}", s))),
         };
 
         Ok(r)
@@ -50,12 +50,15 @@ impl Op {
 }
 
 impl FromStr for Op {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let op = if let Ok(r) = s.parse() {
             Op::Reg(r)
+        } else if let Ok(v) = s.parse() {
+            Op::Imm(v)
         } else {
-            Op::Imm(s.parse().unwrap())
+            let err = format!("expected a register or number, found {:?}", s);
+            return Err(ParseError::new(0, 0, err));
         };
         Ok(op)
     }
@@ -72,7 +75,7 @@ pub enum Instr {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-enum AluErr {
+pub enum AluErr {
     NeedInput(Reg),
 }
 
@@ -121,14 +124,22 @@ impl Instr {
     }
 }
 
+fn operand<T>(s: &str, term: Option<&str>) -> Result<T, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    let term = term.ok_or_else(|| ParseError::new(0, s.len(), "missing operand"))?;
+    term.parse().map_err(|e: ParseError| e.within(0, s, term))
+}
+
 impl FromStr for Instr {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut terms = s.split_whitespace();
-        let instr = match terms.next().unwrap() {
+        let instr = match terms.next().unwrap_or(s) {
             opcode @ ("mul" | "eql" | "add" | "div" | "mod") => {
-                let l = terms.next().unwrap().parse().unwrap();
-                let r = terms.next().unwrap().parse().unwrap();
+                let l = operand(s, terms.next())?;
+                let r = operand(s, terms.next())?;
                 match opcode {
                     "mul" => Instr::Mul(l, r),
                     "eql" => Instr::Eq(l, r),
@@ -138,9 +149,10 @@ impl FromStr for Instr {
                     _ => unreachable!(),
                 }
             }
-            "inp" => Instr::Inp(terms.next().unwrap().parse().unwrap()),
-            s => {
-                panic!("{}", s)
+            "inp" => Instr::Inp(operand(s, terms.next())?),
+            opcode => {
+                let err = format!("unknown opcode {:?}", opcode);
+                return Err(ParseError::at(0, s, opcode, err));
             }
         };
 
@@ -148,8 +160,8 @@ impl FromStr for Instr {
     }
 }
 
-#[derive(Hash, PartialEq, Eq, Clone)]
-struct Alu {
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub struct Alu {
     x: i32,
     y: i32,
     z: i32,
@@ -168,7 +180,7 @@ impl Default for Alu {
 }
 
 impl Alu {
-    fn reg(&self, r: &Reg) -> &i32 {
+    pub fn reg(&self, r: &Reg) -> &i32 {
         match r {
             Reg::X => &self.x,
             Reg::Y => &self.y,
@@ -185,7 +197,8 @@ impl Alu {
         }
     }
 
-    fn run<I>(&mut self, instr: &[Instr], mut input: I) -> Result<(), AluErr>
+    /// Run `instr` until it finishes or wants more input than `input` has.
+    pub fn run<I>(&mut self, instr: &[Instr], mut input: I) -> Result<(), AluErr>
     where
         I: Iterator<Item = i32>,
     {
@@ -197,7 +210,7 @@ impl Alu {
 
 fn digits(v: &Vec<Instr>, max: bool) -> u64 {
     for x in v {
-        println!("{:?}", x);
+        log::trace!("{:?}", x);
     }
     //Split up all instructions into sets separated by input requests
     let instr_ranges = v
@@ -249,7 +262,7 @@ fn digits(v: &Vec<Instr>, max: bool) -> u64 {
             }
         }
         prev_z = valid_z;
-        println!("{} Prev Z: {:?}", i, prev_z.len());
+        log::debug!("{} Prev Z: {:?}", i, prev_z.len());
     }
 
     let remain = prev_z.iter().map(|(_alu, digits)| digits);
@@ -263,15 +276,15 @@ impl Day for Solution {
     type Sol1 = u64;
     type Sol2 = u64;
 
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
-        let mut instr: Vec<_> = r
+        let mut instr = r
             .lines()
-            .flatten()
-            .map(|l| l.parse().unwrap())
-            .collect::<Vec<_>>();
+            .enumerate()
+            .map(|(i, l)| Ok(l?.parse().map_err(|e: ParseError| e.offset(i, 0))?))
+            .collect::<Result<Vec<Instr>, DayError>>()?;
 
         // Useless instructions
         instr.retain(|i| match i {
@@ -297,7 +310,7 @@ impl Day for Solution {
                 }
             ).map(|(j, _)| j).next();
                 if let Some(t) = target {
-                    println!("Swapped {}:{:?} {}:{:?}", t, instr[t], i, instr[i]);
+                    log::trace!("Swapped {}:{:?} {}:{:?}", t, instr[t], i, instr[i]);
                     instr.swap(t, i);
                 }
             }
@@ -368,15 +381,30 @@ mod test {
         //unimplemented!()
     }
     #[test]
+    fn bad_input() {
+        let input = "inp w\n\
+                     add x 2\n\
+                     mul y q";
+        match Solution::process_input(std::io::BufReader::new(input.as_bytes())) {
+            Err(DayError::Parse(e)) => assert_eq!((e.line, e.column), (2, 6)),
+            _ => panic!("expected a parse error"),
+        }
+        let input = "jmp x 2";
+        match Solution::process_input(std::io::BufReader::new(input.as_bytes())) {
+            Err(DayError::Parse(e)) => assert_eq!((e.line, e.column), (0, 0)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+    #[test]
     fn p1() {
-        let input = std::fs::read("input/day24.txt").unwrap();
+        let input = std::fs::read(Solution::input()).unwrap();
         let input = Solution::process_input(std::io::BufReader::new(input.as_slice())).unwrap();
 
         assert_eq!(Solution::p1(&input), 39494195799979);
     }
     #[test]
     fn p2() {
-        let input = std::fs::read("input/day24.txt").unwrap();
+        let input = std::fs::read(Solution::input()).unwrap();
         let input = Solution::process_input(std::io::BufReader::new(input.as_slice())).unwrap();
 
         assert_eq!(Solution::p2(&input), 13161151139617);
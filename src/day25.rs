@@ -1,8 +1,9 @@
+use crate::error::{DayError, ParseError};
+use crate::grid::Grid;
 use crate::Day;
-use cgmath::{Point2, Vector2};
+use cgmath::Vector2;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 #[derive(Eq, PartialEq, Copy, Clone, Hash)]
 pub enum Square {
@@ -24,18 +25,12 @@ impl std::fmt::Display for Square {
 
 #[derive(Clone, Eq, Hash, PartialEq)]
 pub struct Floor {
-    width: usize,
-    tiles: Vec<Square>,
+    tiles: Grid<Square>,
 }
 
 impl std::fmt::Display for Floor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        self.tiles.chunks_exact(self.width).try_for_each(|c| {
-            for x in c {
-                write!(f, "{}", x)?;
-            }
-            writeln!(f, "")
-        })
+        write!(f, "{}", self.tiles)
     }
 }
 
@@ -46,69 +41,41 @@ impl std::fmt::Debug for Floor {
 }
 
 impl Floor {
-    fn new(height: usize, width: usize) -> Floor {
-        Floor {
-            width,
-            tiles: vec![Square::Empty; width * height],
-        }
-    }
-    fn from(width: usize, tiles: Vec<Square>) -> Floor {
-        assert_eq!(tiles.len() % width, 0);
-        Floor { width, tiles }
-    }
-    fn tile(&self, p: &Point2<i32>) -> &Square {
-        &self.tiles[self.offset(*p)]
-    }
-    fn tile_mut(&mut self, p: &Point2<i32>) -> &mut Square {
-        let offset = self.offset(*p);
-        &mut self.tiles[offset]
-    }
-
-    fn pt(&self, v: usize) -> Point2<i32> {
-        Point2::new((v % self.width) as i32, (v / self.width) as i32)
-    }
-    fn height(&self) -> usize {
-        self.tiles.len() / self.width
-    }
-    fn offset(&self, p: Point2<i32>) -> usize {
-        let x = ((p.x as usize % self.width) + self.width) % self.width;
-        let y = ((p.y as usize % self.height()) + self.height()) % self.height();
-        y * self.width + x
-    }
-
-    fn step(self) -> Floor {
-        let mut new = Floor::new(self.height(), self.width);
+    /// Move every herd of sea cucumbers once.
+    pub fn step(self) -> Floor {
+        let (width, height) = (self.tiles.width(), self.tiles.height());
+        let mut new = Grid::new(width, height, Square::Empty);
 
-        self.tiles.iter().enumerate().for_each(|(i, v)| match v {
-            Square::Down => new.tiles[i] = Square::Down,
+        self.tiles.iter().for_each(|(p, v)| match v {
+            Square::Down => new[p] = Square::Down,
             Square::Right => {
-                let target = self.pt(i) + Vector2::new(1, 0);
-                if *self.tile(&target) == Square::Empty {
-                    *new.tile_mut(&target) = *v;
+                let target = p + Vector2::new(1, 0);
+                if *self.tiles.wrapping(target) == Square::Empty {
+                    *new.wrapping_mut(target) = *v;
                 } else {
-                    new.tiles[i] = *v;
+                    new[p] = *v;
                 }
             }
             _ => {}
         });
 
         let new = new;
-        let mut new_down = Floor::new(self.height(), self.width);
+        let mut new_down = Grid::new(width, height, Square::Empty);
 
-        new.tiles.iter().enumerate().for_each(|(i, v)| match v {
-            Square::Right => new_down.tiles[i] = Square::Right,
+        new.iter().for_each(|(p, v)| match v {
+            Square::Right => new_down[p] = Square::Right,
             Square::Down => {
-                let target = self.pt(i) + Vector2::new(0, 1);
-                if *new.tile(&target) == Square::Empty {
-                    *new_down.tile_mut(&target) = *v;
+                let target = p + Vector2::new(0, 1);
+                if *new.wrapping(target) == Square::Empty {
+                    *new_down.wrapping_mut(target) = *v;
                 } else {
-                    new_down.tiles[i] = *v;
+                    new_down[p] = *v;
                 }
             }
             _ => {}
         });
 
-        new_down
+        Floor { tiles: new_down }
     }
 
     fn iter(&self) -> FloorIter {
@@ -130,22 +97,15 @@ impl Iterator for FloorIter {
 }
 
 impl std::str::FromStr for Floor {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut width = None;
-        let tiles = s
-            .lines()
-            .flat_map(|l| {
-                width = Some(l.chars().count());
-                l.chars().collect::<Vec<_>>().into_iter().map(|x| match x {
-                    '>' => Square::Right,
-                    'v' => Square::Down,
-                    '.' => Square::Empty,
-                    _ => panic!(),
-                })
-            })
-            .collect();
-        Ok(Floor::from(width.unwrap(), tiles))
+        let tiles = Grid::from_chars(s, |c| match c {
+            '>' => Ok(Square::Right),
+            'v' => Ok(Square::Down),
+            '.' => Ok(Square::Empty),
+            _ => Err("expected >, v or ."),
+        })?;
+        Ok(Floor { tiles })
     }
 }
 
@@ -156,13 +116,13 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(mut r: R) -> IoResult<Self::Input>
+    fn process_input<R>(mut r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {
         let mut buf = String::new();
-        r.read_to_string(&mut buf).unwrap();
-        Ok(buf.parse::<Floor>().unwrap())
+        r.read_to_string(&mut buf)?;
+        Ok(buf.parse::<Floor>()?)
     }
 
     fn p1(v: &Self::Input) -> Self::Sol1 {
@@ -200,8 +160,10 @@ mod test {
             .parse::<Floor>()
             .unwrap();
 
-        floor.tiles.iter().enumerate().for_each(|(i, _)| {
-            assert_eq!(floor.pt(i), floor.pt(floor.offset(floor.pt(i))));
+        let size = Vector2::new(floor.tiles.width() as i32, floor.tiles.height() as i32);
+        floor.tiles.points().for_each(|p| {
+            assert_eq!(p, floor.tiles.wrap(p + size));
+            assert_eq!(p, floor.tiles.wrap(p - size));
         });
 
         let mut floor = floor.iter();
@@ -263,11 +225,11 @@ mod test {
                      v.v..>>v.v\n\
                      ....v..v.>";
         let floor = input.parse::<Floor>().unwrap();
-        assert_eq!(floor.width, 10);
-        assert_eq!(floor.height(), 9);
+        assert_eq!(floor.tiles.width(), 10);
+        assert_eq!(floor.tiles.height(), 9);
         let test_floor = floor.iter().skip(0).next().unwrap();
-        assert_eq!(test_floor.width, 10);
-        assert_eq!(test_floor.height(), 9);
+        assert_eq!(test_floor.tiles.width(), 10);
+        assert_eq!(test_floor.tiles.height(), 9);
 
         assert_eq!(
             floor.iter().skip(0).next().unwrap(),
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A problem with the puzzle input. Lines and columns are zero based and
+/// relative to whatever was being parsed; the day is filled in by the runner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub day: Option<u32>,
+    pub line: usize,
+    pub column: usize,
+    pub msg: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(line: usize, column: usize, msg: S) -> ParseError {
+        ParseError {
+            day: None,
+            line,
+            column,
+            msg: msg.into(),
+        }
+    }
+
+    /// An error at `field`, which is a slice of `text` starting on `line`.
+    pub fn at<S: Into<String>>(line: usize, text: &str, field: &str, msg: S) -> ParseError {
+        let (l, column) = position(text, field);
+        ParseError::new(line + l, column, msg)
+    }
+
+    /// Parse `field`, which is a slice of `text` starting on `line`,
+    /// reporting failures at its position.
+    pub fn parse<T>(line: usize, text: &str, field: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        field
+            .parse()
+            .map_err(|e| ParseError::at(line, text, field, format!("{:?}: {}", field, e)))
+    }
+
+    /// Move an error from a sub-parser to where its input started.
+    pub fn offset(mut self, line: usize, column: usize) -> ParseError {
+        if self.line == 0 {
+            self.column += column;
+        }
+        self.line += line;
+        self
+    }
+
+    /// Move an error from parsing `field` to where it is in `text`, which
+    /// starts on `line`.
+    pub fn within(self, line: usize, text: &str, field: &str) -> ParseError {
+        let (l, column) = position(text, field);
+        self.offset(line + l, column)
+    }
+
+    pub fn day(mut self, day: u32) -> ParseError {
+        self.day.get_or_insert(day);
+        self
+    }
+}
+
+/// Line and column of `field` within `text`. `field` must be a slice of
+/// `text`, otherwise the start of `text` is reported.
+pub fn position(text: &str, field: &str) -> (usize, usize) {
+    let offset = (field.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+    let before = text.get(..offset).unwrap_or("");
+    let line = before.matches('\n').count();
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (line, column)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line + 1,
+            self.column + 1,
+            self.msg
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Everything that can go wrong while solving a day, short of a solver panicking.
+#[derive(Debug)]
+pub enum DayError {
+    Io(std::io::Error),
+    Input(PathBuf, std::io::Error),
+    Parse(ParseError),
+    UnknownPart(u32),
+}
+
+impl fmt::Display for DayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayError::Io(e) => write!(f, "{}", e),
+            DayError::Input(p, e) => write!(f, "Unable to read input {}: {}", p.display(), e),
+            DayError::Parse(e) => write!(f, "Bad input on {}", e),
+            DayError::UnknownPart(p) => write!(f, "Unknown Part {}", p),
+        }
+    }
+}
+
+impl std::error::Error for DayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DayError::Io(e) | DayError::Input(_, e) => Some(e),
+            DayError::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DayError {
+    fn from(e: std::io::Error) -> Self {
+        DayError::Io(e)
+    }
+}
+
+impl From<ParseError> for DayError {
+    fn from(e: ParseError) -> Self {
+        DayError::Parse(e)
+    }
+}
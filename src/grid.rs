@@ -0,0 +1,259 @@
+//! A rectangular grid of cells, as drawn by the character maps of many puzzles.
+use crate::error::ParseError;
+use cgmath::{Point2, Vector2};
+use std::ops::{Index, IndexMut};
+
+/// The four orthogonal steps, clockwise from up.
+pub(crate) const ORTHOGONAL: [Vector2<i32>; 4] = [
+    Vector2::new(0, -1),
+    Vector2::new(1, 0),
+    Vector2::new(0, 1),
+    Vector2::new(-1, 0),
+];
+
+/// The eight steps to every adjacent cell, in reading order.
+const ADJACENT: [Vector2<i32>; 8] = [
+    Vector2::new(-1, -1),
+    Vector2::new(0, -1),
+    Vector2::new(1, -1),
+    Vector2::new(-1, 0),
+    Vector2::new(1, 0),
+    Vector2::new(-1, 1),
+    Vector2::new(0, 1),
+    Vector2::new(1, 1),
+];
+
+/// Cells stored row by row, addressed by `Point2 { x: column, y: row }` with
+/// `(0, 0)` in the top left. Points are signed so that stepping off an edge
+/// gives `None` from [`Grid::get`] rather than an overflow.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// A grid with every cell set from its position.
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Grid<T>
+    where
+        F: FnMut(Point2<i32>) -> T,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Point2::new(x as i32, y as i32)))
+            .map(&mut f)
+            .collect();
+        Grid { width, cells }
+    }
+
+    /// Parse a character map, one row per line, with `cell` turning each
+    /// character into a cell or explaining what was expected instead. Every
+    /// line must be the same width.
+    pub fn from_chars<F, E>(s: &str, mut cell: F) -> Result<Grid<T>, ParseError>
+    where
+        F: FnMut(char) -> Result<T, E>,
+        E: Into<String>,
+    {
+        let mut width = None;
+        let mut cells = vec![];
+        for (y, l) in s.lines().enumerate() {
+            let width = *width.get_or_insert(l.chars().count());
+            if l.chars().count() != width {
+                return Err(ParseError::new(y, 0, format!("expected {} columns", width)));
+            }
+            for (x, c) in l.chars().enumerate() {
+                cells.push(cell(c).map_err(|e| ParseError::new(y, x, e))?);
+            }
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid { width, cells }),
+            _ => Err(ParseError::new(0, 0, "empty grid")),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    /// The number of cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, p: Point2<i32>) -> bool {
+        (0..self.width as i32).contains(&p.x) && (0..self.height() as i32).contains(&p.y)
+    }
+
+    fn offset(&self, p: Point2<i32>) -> Option<usize> {
+        self.contains(p)
+            .then(|| p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: Point2<i32>) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point2<i32>) -> Option<&mut T> {
+        self.offset(p).map(move |i| &mut self.cells[i])
+    }
+
+    /// Where `p` lands if the grid repeats forever in every direction.
+    pub fn wrap(&self, p: Point2<i32>) -> Point2<i32> {
+        Point2::new(
+            p.x.rem_euclid(self.width as i32),
+            p.y.rem_euclid(self.height() as i32),
+        )
+    }
+
+    /// The cell at `p`, wrapping around the edges.
+    pub fn wrapping(&self, p: Point2<i32>) -> &T {
+        &self[self.wrap(p)]
+    }
+
+    pub fn wrapping_mut(&mut self, p: Point2<i32>) -> &mut T {
+        let p = self.wrap(p);
+        &mut self[p]
+    }
+
+    /// Every position, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point2<i32>> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Point2::new((i % width) as i32, (i / width) as i32))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point2<i32>, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// Every cell, row by row.
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.width)
+    }
+
+    /// Each column, top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.cells.iter().skip(x).step_by(self.width))
+    }
+
+    /// The up to four cells that share an edge with `p`.
+    pub fn neighbours4(&self, p: Point2<i32>) -> impl Iterator<Item = Point2<i32>> + '_ {
+        ORTHOGONAL
+            .iter()
+            .map(move |d| p + d)
+            .filter(|p| self.contains(*p))
+    }
+
+    /// The up to eight cells that share an edge or a corner with `p`.
+    pub fn neighbours8(&self, p: Point2<i32>) -> impl Iterator<Item = Point2<i32>> + '_ {
+        ADJACENT
+            .iter()
+            .map(move |d| p + d)
+            .filter(|p| self.contains(*p))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Point2<i32>> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point2<i32>) -> &T {
+        match self.get(p) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside of the grid", p),
+        }
+    }
+}
+
+impl<T> IndexMut<Point2<i32>> for Grid<T> {
+    fn index_mut(&mut self, p: Point2<i32>) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{:?} is outside of the grid", p),
+        }
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.rows().try_for_each(|row| {
+            row.iter().try_for_each(|c| write!(f, "{}", c))?;
+            writeln!(f)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test() {
+        let grid =
+            Grid::from_chars("123\n456", |c| c.to_digit(10).ok_or("expected a digit")).unwrap();
+        assert_eq!((grid.width(), grid.height(), grid.len()), (3, 2, 6));
+        assert_eq!(grid.to_string(), "123\n456\n");
+        assert_eq!(grid[Point2::new(2, 1)], 6);
+        assert_eq!(grid.get(Point2::new(3, 0)), None);
+        assert_eq!(grid.get(Point2::new(0, -1)), None);
+        assert_eq!(*grid.wrapping(Point2::new(-1, 2)), 3);
+        assert_eq!(grid.wrap(Point2::new(7, -3)), Point2::new(1, 1));
+
+        let neighbours =
+            |ps: &mut dyn Iterator<Item = Point2<i32>>| ps.map(|p| grid[p]).collect::<Vec<_>>();
+        assert_eq!(neighbours(&mut grid.neighbours4(Point2::new(0, 0))), [2, 4]);
+        assert_eq!(
+            neighbours(&mut grid.neighbours8(Point2::new(1, 1))),
+            [1, 2, 3, 4, 6]
+        );
+
+        let rows = grid.rows().map(|r| r.to_vec()).collect::<Vec<_>>();
+        assert_eq!(rows, [[1, 2, 3], [4, 5, 6]]);
+        let columns = grid
+            .columns()
+            .map(|c| c.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(
+            grid.map(|d| d * 2),
+            Grid::from_fn(3, 2, |p| 2 * (p.y * 3 + p.x + 1) as u32)
+        );
+
+        let err = Grid::from_chars("12\n3", |c| c.to_digit(10).ok_or("expected a digit"));
+        assert_eq!(err, Err(ParseError::new(1, 0, "expected 2 columns")));
+        let err = Grid::from_chars("12\n3x", |c| c.to_digit(10).ok_or("expected a digit"));
+        assert_eq!(err, Err(ParseError::new(1, 1, "expected a digit")));
+    }
+}
@@ -0,0 +1,339 @@
+//! Advent of Code solutions, mostly 2021. Each day is a [`Day`], found through
+//! the [`tests`] registry by year and day, and the more interesting puzzle
+//! types are re-exported here for use outside of the `aoc2021` runner.
+
+pub mod answers;
+pub mod bench;
+pub mod error;
+pub mod grid;
+pub mod ocr;
+pub mod path;
+pub mod render;
+
+use bench::{BenchOpts, Timings};
+use error::DayError;
+use rayon::prelude::*;
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub use day16::{
+    BitsError, BitsErrorKind, LengthType, Mode, Notation, Packet, PacketData, MAX_DEPTH,
+};
+pub use day18::SnailFish;
+pub use day19::Scanner;
+pub use day22::Cube;
+pub use day23::Burrow;
+pub use day24::{Alu, AluErr, Instr};
+pub use day25::Floor;
+
+/// Where a day should read its puzzle input from.
+pub enum InputSource {
+    /// `<year>/dayNN.txt` in the input directory, see [`Day::input`].
+    Default,
+    /// The example input of this name, see [`Day::example`].
+    Example(String),
+    Path(PathBuf),
+    /// Input that has already been read, e.g. from stdin.
+    Bytes(Vec<u8>),
+}
+
+/// The answer to a single part, along with how long it took to get there.
+pub struct Answer {
+    pub part: u32,
+    /// The answer, or why the solver panicked.
+    pub answer: Result<String, String>,
+    pub solve: Duration,
+}
+
+/// Every part asked for, solved from a single parse of the input.
+pub struct Solved {
+    pub parse: Duration,
+    pub parts: Vec<Answer>,
+}
+
+/// The year a [`Day`] belongs to unless it says otherwise.
+pub const DEFAULT_YEAR: u32 = 2021;
+
+/// Where the puzzle input for `year` and `day` is expected, see [`Day::input`].
+pub fn input_path(year: u32, day: u32) -> PathBuf {
+    let dir = std::env::var_os("AOC_INPUT_DIR").unwrap_or_else(|| "input".into());
+    PathBuf::from(dir)
+        .join(year.to_string())
+        .join(format!("day{:02}.txt", day))
+}
+
+/// Where the example inputs for `year` and `day` are kept, along with their
+/// expected answers in `answers.toml`. Like the modules in `src/`, this is
+/// `test/dayNN/` for the default year and `test/yearNNNN/dayNN/` otherwise.
+pub fn example_dir(year: u32, day: u32) -> PathBuf {
+    let mut dir = PathBuf::from("test");
+    if year != DEFAULT_YEAR {
+        dir.push(format!("year{}", year));
+    }
+    dir.join(format!("day{:02}", day))
+}
+
+/// A single day's puzzle: how to parse the input and solve both parts.
+pub trait Day
+where
+    Self: 'static,
+{
+    const YEAR: u32 = DEFAULT_YEAR;
+    const DAY: u32;
+    type Input: Sync;
+    type Sol1: std::fmt::Display;
+    type Sol2: std::fmt::Display;
+    /// The default input file, `input/<year>/dayNN.txt`. The directory can
+    /// be changed with the `AOC_INPUT_DIR` environment variable.
+    fn input() -> PathBuf {
+        input_path(Self::YEAR, Self::DAY)
+    }
+    /// The example input `name`, `<name>.txt` in the [`example_dir`].
+    fn example(name: &str) -> PathBuf {
+        example_dir(Self::YEAR, Self::DAY)
+            .join(name)
+            .with_extension("txt")
+    }
+    /// Parse the input once, then solve each of `parts` from it, concurrently
+    /// on the current rayon pool. A panic in one part is reported in its
+    /// [`Answer`] rather than losing the others.
+    fn run(parts: &[u32], input: &InputSource) -> Result<Solved, DayError> {
+        check_parts(parts)?;
+        let start = Instant::now();
+        let v = Self::parse(input)?;
+        let parse = start.elapsed();
+
+        let parts = parts
+            .par_iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let answer = std::panic::catch_unwind(AssertUnwindSafe(|| match part {
+                    1 => Self::p1(&v).to_string(),
+                    _ => Self::p2(&v).to_string(),
+                }))
+                .map_err(panic_message);
+                Answer {
+                    part,
+                    answer,
+                    solve: start.elapsed(),
+                }
+            })
+            .collect();
+        Ok(Solved { parse, parts })
+    }
+    /// Parse the input once, then time each part `opts.runs` times.
+    fn bench(parts: &[u32], input: &InputSource, opts: &BenchOpts) -> Result<Timings, DayError> {
+        check_parts(parts)?;
+        let start = Instant::now();
+        let v = Self::parse(input)?;
+        let parse = start.elapsed();
+
+        let parts = parts
+            .iter()
+            .map(|&part| {
+                let once = || {
+                    let start = Instant::now();
+                    match part {
+                        1 => drop(std::hint::black_box(Self::p1(&v))),
+                        _ => drop(std::hint::black_box(Self::p2(&v))),
+                    }
+                    start.elapsed()
+                };
+                for _ in 0..opts.warmup {
+                    once();
+                }
+                (part, (0..opts.runs).map(|_| once()).collect())
+            })
+            .collect();
+        Ok(Timings { parse, parts })
+    }
+    /// Solve a part from any reader, e.g. for a test harness.
+    fn solve_part<R>(part: u32, r: R) -> Result<String, DayError>
+    where
+        R: std::io::BufRead,
+    {
+        check_parts(&[part])?;
+        let v = Self::process_input(r)?;
+        Ok(match part {
+            1 => Self::p1(&v).to_string(),
+            _ => Self::p2(&v).to_string(),
+        })
+    }
+    /// Read and parse `input`, with any parse error marked with the day.
+    fn parse(input: &InputSource) -> Result<Self::Input, DayError> {
+        let v = match input {
+            InputSource::Bytes(b) => Self::process_input(b.as_slice()),
+            InputSource::Default | InputSource::Example(_) | InputSource::Path(_) => {
+                let path = match input {
+                    InputSource::Path(p) => p.clone(),
+                    InputSource::Example(name) => Self::example(name),
+                    _ => Self::input(),
+                };
+                let f = std::fs::File::open(&path).map_err(|e| DayError::Input(path, e))?;
+                Self::process_input(BufReader::new(f))
+            }
+        };
+        v.map_err(|e| match e {
+            DayError::Parse(e) => DayError::Parse(e.day(Self::DAY)),
+            e => e,
+        })
+    }
+    fn process_input<R>(r: R) -> Result<Self::Input, DayError>
+    where
+        R: std::io::BufRead;
+    fn p1(_input: &Self::Input) -> Self::Sol1 {
+        unimplemented!("Missing implementation of Day {} Part 1", Self::DAY)
+    }
+    fn p2(_input: &Self::Input) -> Self::Sol2 {
+        unimplemented!("Missing implementation of Day {} Part 2", Self::DAY)
+    }
+}
+
+fn check_parts(parts: &[u32]) -> Result<(), DayError> {
+    match parts.iter().find(|p| !(1..=2).contains(*p)) {
+        Some(p) => Err(DayError::UnknownPart(*p)),
+        None => Ok(()),
+    }
+}
+
+pub type RunFn = fn(&[u32], &InputSource) -> Result<Solved, DayError>;
+pub type BenchFn = fn(&[u32], &InputSource, &BenchOpts) -> Result<Timings, DayError>;
+/// Declares the day modules and the registry of them. Days of the default year
+/// live in `src/`, other years are grouped as `yearNNNN { dayNN, ... }` and
+/// live in `src/yearNNNN/`.
+macro_rules! tests {
+    ($($name:ident),* $(; $year:ident { $($year_name:ident),* })*) => {
+        $(
+            pub mod $name;
+        )*
+        $(
+            pub mod $year {
+                $(
+                    pub mod $year_name;
+                )*
+            }
+        )*
+        /// Every day in the crate, keyed by ([`Day::YEAR`], [`Day::DAY`]).
+        pub fn tests() -> HashMap<(u32, u32), RunFn> {
+            [
+                $(
+                    (($name::Solution::YEAR, $name::Solution::DAY), $name::Solution::run as RunFn),
+                )*
+                $($(
+                    (
+                        ($year::$year_name::Solution::YEAR, $year::$year_name::Solution::DAY),
+                        $year::$year_name::Solution::run as RunFn,
+                    ),
+                )*)*
+            ].into_iter().collect()
+        }
+        /// The [`Day::bench`] of every day in the crate, keyed like [`tests`].
+        pub fn benches() -> HashMap<(u32, u32), BenchFn> {
+            [
+                $(
+                    (($name::Solution::YEAR, $name::Solution::DAY), $name::Solution::bench as BenchFn),
+                )*
+                $($(
+                    (
+                        ($year::$year_name::Solution::YEAR, $year::$year_name::Solution::DAY),
+                        $year::$year_name::Solution::bench as BenchFn,
+                    ),
+                )*)*
+            ].into_iter().collect()
+        }
+    }
+}
+
+tests!(
+    template, day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12,
+    day13, day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25
+);
+
+/// Runs a day, turning a panic while parsing into an error so that one
+/// broken day doesn't take down the rest of the run.
+pub fn run_parts(f: RunFn, parts: &[u32], input: &InputSource) -> Result<Solved, String> {
+    match std::panic::catch_unwind(|| f(parts, input)) {
+        Ok(Ok(solved)) => Ok(solved),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(panic) => Err(panic_message(panic)),
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use answers::Examples;
+
+    /// Run every example input of every day, checking the parts that have an
+    /// expected answer.
+    #[test]
+    fn examples() {
+        let mut checked = 0;
+        let mut failures = vec![];
+        for ((year, day), f) in tests() {
+            let dir = example_dir(year, day);
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let answers = Examples::load(dir.join("answers.toml")).unwrap();
+            for entry in entries {
+                let path = entry.unwrap().path();
+                if path.extension().is_none_or(|e| e != "txt") {
+                    continue;
+                }
+                let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                let expected = [1, 2]
+                    .into_iter()
+                    .filter_map(|part| Some((part, answers.get(&name, part)?)))
+                    .collect::<Vec<_>>();
+                if expected.is_empty() {
+                    failures.push(format!("{}: no expected answers", path.display()));
+                    continue;
+                }
+                let parts = expected.iter().map(|(part, _)| *part).collect::<Vec<_>>();
+                let solved = match run_parts(f, &parts, &InputSource::Example(name)) {
+                    Ok(solved) => solved,
+                    Err(e) => {
+                        failures.push(format!("{}: {}", path.display(), e));
+                        continue;
+                    }
+                };
+                for (answer, (part, expected)) in solved.parts.iter().zip(&expected) {
+                    checked += 1;
+                    if answer.answer.as_deref() != Ok(*expected) {
+                        failures.push(format!(
+                            "{} part {}: expected {}, got {:?}",
+                            path.display(),
+                            part,
+                            expected,
+                            answer.answer
+                        ));
+                    }
+                }
+            }
+        }
+        assert!(checked > 0, "No examples found");
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn solve_part() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let solve = |part| day01::Solution::solve_part(part, input.as_bytes());
+        assert_eq!(solve(1).unwrap(), "7");
+        assert_eq!(solve(2).unwrap(), "5");
+        assert!(matches!(solve(3), Err(DayError::UnknownPart(3))));
+    }
+}
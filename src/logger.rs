@@ -0,0 +1,57 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Writes log records to stderr so that stdout only carries results.
+struct Stderr;
+
+static LOGGER: Stderr = Stderr;
+
+impl Log for Stderr {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{:<5} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Log at `info` by default, one level more per `-v` and only errors when
+/// `quiet`.
+pub fn init(verbose: u64, quiet: bool) {
+    let level = if quiet {
+        LevelFilter::Error
+    } else {
+        match verbose {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    };
+    // Only fails if a logger is already set, which leaves that one in place.
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use log::Level;
+    #[test]
+    fn test() {
+        init(1, false);
+        assert!(log::log_enabled!(Level::Debug));
+        assert!(!log::log_enabled!(Level::Trace));
+        init(3, true);
+        assert!(log::log_enabled!(Level::Error));
+        assert!(!log::log_enabled!(Level::Warn));
+    }
+}
@@ -1,98 +1,816 @@
-use clap::Parser;
+mod alloc;
+mod logger;
+mod report;
+mod scaffold;
+
+use aoc2021::answers::Answers;
+use aoc2021::bench::{self, BenchOpts, Stats};
+use aoc2021::{
+    benches, day15, input_path, render, run_parts, tests, Day, InputSource, Mode, Notation, Packet,
+    RunFn, Solved,
+};
+use clap::{Args, Parser, Subcommand};
+use rayon::prelude::*;
+use report::Format;
+use serde::Serialize;
 use std::collections::HashMap;
-use std::io::BufReader;
-use std::io::Result as IoResult;
+use std::io::Read;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[global_allocator]
+static ALLOC: alloc::Counting = alloc::Counting;
 
 #[derive(Parser)]
 #[clap(version = "1.0", author = "Mitch Souders <crzysdrs@gmail.com>")]
 struct Opts {
-    test: u32,
-    part: u32,
+    /// The event year to run days from
+    #[clap(long, global = true, default_value = "2021")]
+    year: u32,
+    /// How to print results: `table`, `json`, `csv` or `tap`
+    #[clap(long, global = true, default_value = "table")]
+    format: Format,
+    /// Log solver diagnostics to stderr, `-vv` for even more detail
+    #[clap(short, long, global = true, parse(from_occurrences))]
+    verbose: u64,
+    /// Only log errors
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    #[clap(subcommand)]
+    cmd: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Run a single day, a range of days (`10..=20`) or `all`
+    Run {
+        days: Days,
+        /// `1`, `2` or `both`, which parses the input only once
+        part: Option<Parts>,
+        /// Read the puzzle input from this file instead (`-` for stdin)
+        #[clap(long)]
+        input: Option<PathBuf>,
+        /// Run each day on its example input of this name, `test/dayNN/<name>.txt`
+        #[clap(long, conflicts_with = "input")]
+        example: Option<String>,
+        #[clap(flatten)]
+        harness: Harness,
+    },
+    /// Check every day against the stored answers
+    Verify {
+        #[clap(long, default_value = "answers.toml")]
+        answers: PathBuf,
+        #[clap(flatten)]
+        harness: Harness,
+    },
+    /// Time a day's solvers over repeated runs of an already parsed input
+    Bench {
+        day: u32,
+        part: Option<Parts>,
+        /// Untimed runs before measuring
+        #[clap(long, default_value = "3")]
+        warmup: usize,
+        #[clap(long, default_value = "10")]
+        runs: usize,
+        /// Read the puzzle input from this file instead (`-` for stdin)
+        #[clap(long)]
+        input: Option<PathBuf>,
+        /// Write the results as JSON
+        #[clap(long)]
+        save: Option<PathBuf>,
+        /// Compare against results saved by an earlier `--save`
+        #[clap(long)]
+        compare: Option<PathBuf>,
+    },
+    /// Start a new day from `src/template.rs` with an empty input file
+    New { day: u32 },
+    /// Draw the least risky day 15 route in colour, or to a `.ppm` or `.png` image
+    Route {
+        /// `1` for the map as given, `2` for the map tiled five times each way
+        #[clap(default_value = "1")]
+        part: u32,
+        /// Read the puzzle input from this file instead (`-` for stdin)
+        #[clap(long)]
+        input: Option<PathBuf>,
+        /// Use the example input of this name, `test/day15/<name>.txt`
+        #[clap(long, conflicts_with = "input")]
+        example: Option<String>,
+        /// Write an image here instead of printing to the terminal
+        #[clap(long)]
+        output: Option<PathBuf>,
+        /// The width and height in pixels of each cell of an image
+        #[clap(long, default_value = "4", parse(try_from_str = cell_size))]
+        scale: usize,
+    },
+    /// Take apart day 16 BITS transmissions
+    Bits {
+        #[clap(subcommand)]
+        cmd: BitsCmd,
+    },
+}
+
+#[derive(Subcommand)]
+enum BitsCmd {
+    /// Print the expression a hex transmission encodes, and its value
+    Decode {
+        /// The transmission, such as `C200B40A82`
+        hex: String,
+        /// `infix` or `sexp`
+        #[clap(long, default_value = "infix")]
+        notation: Notation,
+        /// Leave out the version and bit offset of each packet
+        #[clap(long)]
+        plain: bool,
+        /// Reject set padding bits and literals wider than 64 bits
+        #[clap(long)]
+        strict: bool,
+    },
 }
 
-trait Day
-where
-    Self: 'static,
-{
-    const DAY: u32;
-    type Input;
-    type Sol1: std::fmt::Display;
-    type Sol2: std::fmt::Display;
-    fn input() -> PathBuf {
-        let input = PathBuf::from("input").join(format!("day{:02}.txt", Self::DAY));
-        if !input.exists() {
-            panic!("Input does not exist: {}", input.display());
+struct Days(RangeInclusive<u32>);
+
+impl FromStr for Days {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let day = |d: &str| {
+            d.parse::<u32>()
+                .map_err(|_| format!("Invalid day {:?} in {:?}", d, s))
+        };
+        let days = if s == "all" {
+            1..=25
+        } else if let Some((start, end)) = s.split_once("..=") {
+            day(start)?..=day(end)?
+        } else if let Some((start, end)) = s.split_once("..") {
+            let start = day(start)?;
+            match day(end)?.checked_sub(1) {
+                Some(end) => start..=end,
+                None => return Err(format!("Range {:?} is empty", s)),
+            }
+        } else {
+            let d = day(s)?;
+            d..=d
+        };
+        if days.is_empty() {
+            return Err(format!("Range {:?} is empty", s));
         }
-        input
+        Ok(Days(days))
     }
-    fn run_p1() -> IoResult<()> {
-        let mut buf = BufReader::new(std::fs::File::open(Self::input())?);
-        let v = Self::process_input(&mut buf)?;
-        println!("Solution Day {} Part 1", Self::DAY);
-        println!("{}", Self::p1(&v));
-        Ok(())
+}
+
+/// The outcome of running a single part.
+#[derive(Serialize)]
+struct RunRecord {
+    year: u32,
+    day: u32,
+    part: u32,
+    /// `ok` or `failed`
+    status: &'static str,
+    answer: Option<String>,
+    error: Option<String>,
+    /// The parse is shared by every part of a day.
+    parse_ns: Option<u64>,
+    solve_ns: Option<u64>,
+    /// Peak heap use of the whole day.
+    memory_bytes: Option<usize>,
+}
+
+/// The outcome of checking a single part against its stored answer.
+#[derive(Serialize)]
+struct VerifyRecord {
+    year: u32,
+    day: u32,
+    part: u32,
+    /// `ok`, `mismatch`, `failed` or `no answer`
+    status: &'static str,
+    expected: Option<String>,
+    answer: Option<String>,
+    error: Option<String>,
+    parse_ns: Option<u64>,
+    solve_ns: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct BenchRecord<'a> {
+    #[serde(flatten)]
+    stats: &'a Stats,
+    parse_ns: u64,
+    /// Change of the median against `--compare`.
+    change_pct: Option<f64>,
+}
+
+fn nanos(d: Duration) -> u64 {
+    d.as_nanos() as u64
+}
+
+fn show_nanos(ns: Option<u64>) -> String {
+    ns.map_or_else(
+        || "-".into(),
+        |ns| format!("{:.2?}", Duration::from_nanos(ns)),
+    )
+}
+
+/// How to go about solving many days.
+#[derive(Args)]
+struct Harness {
+    /// Number of days and parts to solve at once, defaults to the CPU count.
+    /// The peak memory of each day is only measured with `--jobs 1`
+    #[clap(long)]
+    jobs: Option<usize>,
+    /// Give up on a day after this many seconds. It keeps running in the
+    /// background while the next day starts in its place
+    #[clap(long, parse(try_from_str = seconds))]
+    timeout: Option<Duration>,
+}
+
+fn seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|s| Duration::try_from_secs_f64(s).ok())
+        .ok_or_else(|| format!("Invalid number of seconds {:?}", s))
+}
+
+fn cell_size(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) => Err("The scale must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(_) => Err(format!("Invalid scale {:?}", s)),
     }
-    fn run_p2() -> IoResult<()> {
-        let mut buf = BufReader::new(std::fs::File::open(Self::input())?);
-        let v = Self::process_input(&mut buf)?;
-        println!("Solution Day {} Part 2", Self::DAY);
-        println!("{}", Self::p2(&v));
-        Ok(())
+}
+
+/// Which parts to solve, `both` when none are given.
+struct Parts(Vec<u32>);
+
+impl Default for Parts {
+    fn default() -> Self {
+        Parts(vec![1, 2])
     }
-    fn process_input<R>(r: R) -> IoResult<Self::Input>
-    where
-        R: std::io::BufRead;
-    fn p1(_input: &Self::Input) -> Self::Sol1 {
-        unimplemented!("Missing implementation of Day {} Part 1", Self::DAY)
+}
+
+impl FromStr for Parts {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Parts(vec![1])),
+            "2" => Ok(Parts(vec![2])),
+            "both" => Ok(Parts::default()),
+            _ => Err(format!("Unknown Part {}", s)),
+        }
     }
-    fn p2(_input: &Self::Input) -> Self::Sol2 {
-        unimplemented!("Missing implementation of Day {} Part 2", Self::DAY)
+}
+
+/// A day solved, or why it couldn't be, with its peak heap use if known.
+type DayResult = (Result<Solved, String>, Option<usize>);
+
+/// Solve `parts` of a day, along with the peak heap use of doing so. Days
+/// without any parts to solve aren't run at all.
+fn solve(f: RunFn, parts: &[u32], input: &InputSource) -> DayResult {
+    if parts.is_empty() {
+        let parse = Duration::ZERO;
+        return (
+            Ok(Solved {
+                parse,
+                parts: vec![],
+            }),
+            None,
+        );
     }
+    let (solved, peak) = alloc::peak(|| run_parts(f, parts, input));
+    (solved, Some(peak))
 }
 
-type IoFn = fn() -> IoResult<()>;
-macro_rules! tests {
-    ($($name:ident),*) => {
-        $(
-            mod $name;
-        )*
-        fn tests() -> HashMap<u32, (IoFn, IoFn)> {
-            [
-                $(
-                    (
-                        $name::Solution::DAY,
-                        ($name::Solution::run_p1 as IoFn,
-                         $name::Solution::run_p2 as IoFn)
-                    ),
-                )*
-            ].into_iter().collect()
+/// Solve each day's parts, `jobs` days at a time, in the order given. Peak
+/// heap use is only kept when nothing else could have run alongside a day,
+/// as days share the heap and their peaks can't be told apart.
+///
+/// With a `timeout`, each day runs on a thread of its own and is given up on
+/// if it runs out of time. It keeps running in the background, as there is
+/// no way to cancel it, while the next day takes its place.
+fn run_days(
+    jobs: Option<usize>,
+    days: Vec<(RunFn, Vec<u32>)>,
+    input: &Arc<InputSource>,
+    timeout: Option<Duration>,
+) -> std::io::Result<Vec<DayResult>> {
+    let pool = pool(jobs)?;
+    let jobs = pool.current_num_threads();
+    let Some(timeout) = timeout else {
+        return Ok(pool.install(|| {
+            days.par_iter()
+                .map(|(f, parts)| solve(*f, parts, input))
+                .map(|(solved, peak)| (solved, peak.filter(|_| jobs == 1)))
+                .collect()
+        }));
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut results = days.iter().map(|_| None).collect::<Vec<_>>();
+    let mut queue = days.into_iter().enumerate();
+    // When each running day started, and whether it has the heap to itself.
+    let mut running = HashMap::<usize, (Instant, bool)>::new();
+    // Days that timed out but haven't finished yet.
+    let mut abandoned = 0;
+    loop {
+        while running.len() < jobs {
+            let Some((i, (f, parts))) = queue.next() else {
+                break;
+            };
+            // A pool of its own, so that the day's parts can't pick up the
+            // work of any other day.
+            let own = pool_of(1)?;
+            let (tx, input) = (tx.clone(), input.clone());
+            own.spawn(move || {
+                let _ = tx.send((i, solve(f, &parts, &input)));
+            });
+            let alone = jobs == 1 && abandoned == 0;
+            running.insert(i, (Instant::now(), alone));
+        }
+        let Some(deadline) = running.values().map(|(start, _)| *start + timeout).min() else {
+            break;
+        };
+
+        // The sender is still held here, so this can only time out.
+        if let Ok((i, (solved, peak))) =
+            rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            match running.remove(&i) {
+                Some((_, alone)) => results[i] = Some((solved, peak.filter(|_| alone))),
+                None => abandoned -= 1,
+            }
+        }
+
+        let now = Instant::now();
+        let expired = running
+            .iter()
+            .filter(|(_, (start, _))| now >= *start + timeout)
+            .map(|(i, _)| *i)
+            .collect::<Vec<_>>();
+        for i in expired {
+            running.remove(&i);
+            abandoned += 1;
+            results[i] = Some((Err(format!("timed out after {:.2?}", timeout)), None));
         }
     }
+    Ok(results
+        .into_iter()
+        .map(|r| r.expect("every day is run"))
+        .collect())
 }
 
-tests!(
-    template, day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12,
-    day13, day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25
-);
+/// A pool for solving with, `jobs` threads wide or one per CPU.
+fn pool(jobs: Option<usize>) -> std::io::Result<rayon::ThreadPool> {
+    pool_of(jobs.unwrap_or(0))
+}
+
+fn pool_of(threads: usize) -> std::io::Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(std::io::Error::other)
+}
+
+fn input_source(input: Option<PathBuf>) -> std::io::Result<InputSource> {
+    Ok(match input {
+        None => InputSource::Default,
+        Some(p) if p.as_os_str() == "-" => {
+            // Every part needs the input, so stdin is read up front.
+            let mut buf = vec![];
+            std::io::stdin().read_to_end(&mut buf)?;
+            InputSource::Bytes(buf)
+        }
+        Some(p) => InputSource::Path(p),
+    })
+}
 
 fn main() -> std::io::Result<()> {
     let opts: Opts = Opts::parse();
+    logger::init(opts.verbose, opts.quiet);
 
+    let year = opts.year;
     let sols = tests();
-    if let Some(sol) = sols.get(&opts.test) {
-        match opts.part {
-            1 => (sol.0)()?,
-            2 => (sol.1)()?,
-            p => {
-                let err = format!("Unknown Test (Day {} Part {})", opts.test, p);
-                return Err(std::io::Error::new(std::io::ErrorKind::Other, err));
+    let mut days = sols
+        .iter()
+        .filter(|((y, _), _)| *y == year)
+        .map(|((_, day), f)| (day, f))
+        .collect::<Vec<_>>();
+    days.sort_by_key(|(day, _)| **day);
+
+    match opts.cmd {
+        Cmd::Run {
+            days: selected,
+            part,
+            input,
+            example,
+            harness,
+        } => {
+            let parts = part.unwrap_or_default().0;
+
+            days.retain(|(day, _)| selected.0.contains(day));
+            if days.is_empty() {
+                let err = format!("Unknown Test (Year {}, Days {:?})", year, selected.0);
+                return Err(std::io::Error::other(err));
+            }
+
+            if input.is_some() && days.len() > 1 {
+                let err = "--input can only be used with a single day";
+                return Err(std::io::Error::other(err));
+            }
+            let input = &Arc::new(match example {
+                Some(name) => InputSource::Example(name),
+                None => input_source(input)?,
+            });
+
+            let jobs = days.iter().map(|(_, sol)| (**sol, parts.clone())).collect();
+            let solved = run_days(harness.jobs, jobs, input, harness.timeout)?;
+
+            let mut records = vec![];
+            for ((day, _), (solved, peak)) in days.iter().zip(solved) {
+                let record = |part, parse| RunRecord {
+                    year,
+                    day: **day,
+                    part,
+                    status: "failed",
+                    answer: None,
+                    error: None,
+                    parse_ns: parse,
+                    solve_ns: None,
+                    memory_bytes: peak,
+                };
+                match solved {
+                    Ok(s) => records.extend(s.parts.into_iter().map(|a| {
+                        let r = record(a.part, Some(nanos(s.parse)));
+                        match a.answer {
+                            Ok(answer) => RunRecord {
+                                status: "ok",
+                                answer: Some(answer),
+                                solve_ns: Some(nanos(a.solve)),
+                                ..r
+                            },
+                            Err(e) => RunRecord {
+                                error: Some(e),
+                                ..r
+                            },
+                        }
+                    })),
+                    Err(e) => records.extend(parts.iter().map(|part| RunRecord {
+                        error: Some(e.clone()),
+                        ..record(*part, None)
+                    })),
+                }
+            }
+
+            if opts.format != Format::Table {
+                return report::records(opts.format, &records);
+            }
+            let rows = records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    let answer = match (&r.answer, &r.error) {
+                        (Some(answer), _) => answer.clone(),
+                        (None, e) => format!("FAILED: {}", e.as_deref().unwrap_or_default()),
+                    };
+                    // The parse and memory are shared, so they are only shown
+                    // against the first part of each day.
+                    let (parse, peak) = if i == 0 || records[i - 1].day != r.day {
+                        let peak = r.memory_bytes.map_or_else(|| "-".into(), alloc::human);
+                        (show_nanos(r.parse_ns), peak)
+                    } else {
+                        ("-".into(), "-".into())
+                    };
+                    let solve = show_nanos(r.solve_ns);
+                    [
+                        r.day.to_string(),
+                        r.part.to_string(),
+                        answer,
+                        parse,
+                        solve,
+                        peak,
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            let header = ["Day", "Part", "Answer", "Parse", "Solve", "Memory"];
+            report::table(header, &rows);
+        }
+        Cmd::Verify { answers, harness } => {
+            let answers = Answers::load(&answers)?;
+            days.retain(|(day, _)| (1..=25).contains(*day));
+
+            let input = &Arc::new(InputSource::Default);
+            let jobs = days
+                .iter()
+                .map(|(day, sol)| {
+                    let parts = (1..=2)
+                        .filter(|part| answers.get(year, **day, *part).is_some())
+                        .collect();
+                    (**sol, parts)
+                })
+                .collect();
+            let solved = run_days(harness.jobs, jobs, input, harness.timeout)?
+                .into_iter()
+                .map(|(solved, _)| solved);
+
+            let mut records = vec![];
+            for ((day, _), solved) in days.iter().zip(solved) {
+                for part in 1..=2 {
+                    let expected = answers.get(year, **day, part);
+                    let mut r = VerifyRecord {
+                        year,
+                        day: **day,
+                        part,
+                        status: "no answer",
+                        expected: expected.map(String::from),
+                        answer: None,
+                        error: None,
+                        parse_ns: None,
+                        solve_ns: None,
+                    };
+                    if let Some(expected) = expected {
+                        let solved = solved.as_ref().map(|s| {
+                            let a = s.parts.iter().find(|a| a.part == part);
+                            (s.parse, a.expect("every part with an answer is solved"))
+                        });
+                        match solved {
+                            Ok((parse, a)) => {
+                                r.parse_ns = Some(nanos(parse));
+                                match &a.answer {
+                                    Ok(answer) => {
+                                        r.status =
+                                            if answer == expected { "ok" } else { "mismatch" };
+                                        r.answer = Some(answer.clone());
+                                        r.solve_ns = Some(nanos(a.solve));
+                                    }
+                                    Err(e) => {
+                                        r.status = "failed";
+                                        r.error = Some(e.clone());
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                r.status = "failed";
+                                r.error = Some(e.clone());
+                            }
+                        }
+                    }
+                    records.push(r);
+                }
+            }
+
+            let mismatches = records
+                .iter()
+                .filter(|r| ["mismatch", "failed"].contains(&r.status))
+                .count();
+            if opts.format == Format::Table {
+                let rows = records
+                    .iter()
+                    .map(|r| {
+                        let status = match r.status {
+                            "mismatch" => format!(
+                                "MISMATCH: expected {}, got {}",
+                                r.expected.as_deref().unwrap_or_default(),
+                                r.answer.as_deref().unwrap_or_default()
+                            ),
+                            "failed" => {
+                                format!("FAILED: {}", r.error.as_deref().unwrap_or_default())
+                            }
+                            status => status.to_string(),
+                        };
+                        [r.day.to_string(), r.part.to_string(), status]
+                    })
+                    .collect::<Vec<_>>();
+                report::table(["Day", "Part", "Status"], &rows);
+            } else {
+                report::records(opts.format, &records)?;
+            }
+            if mismatches > 0 {
+                let err = format!("{} answer(s) did not verify", mismatches);
+                return Err(std::io::Error::other(err));
+            }
+        }
+        Cmd::Bench {
+            day,
+            part,
+            warmup,
+            runs,
+            input,
+            save,
+            compare,
+        } => {
+            let parts = part.unwrap_or_default().0;
+            let f = *benches().get(&(year, day)).ok_or_else(|| {
+                std::io::Error::other(format!("Unknown Test (Year {}, Day {})", year, day))
+            })?;
+            let baseline = compare.map(bench::load).transpose()?;
+            let input = input_source(input)?;
+
+            let timings = f(&parts, &input, &BenchOpts { warmup, runs })
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+            let stats = timings
+                .parts
+                .iter()
+                .map(|(part, times)| Stats::new(year, day, *part, times))
+                .collect::<Vec<_>>();
+            let records = stats
+                .iter()
+                .map(|s| BenchRecord {
+                    stats: s,
+                    parse_ns: nanos(timings.parse),
+                    change_pct: baseline
+                        .iter()
+                        .flatten()
+                        .find(|b| (b.year, b.day, b.part) == (s.year, s.day, s.part))
+                        .and_then(|b| s.change(b)),
+                })
+                .collect::<Vec<_>>();
+
+            if opts.format == Format::Table {
+                println!("Day {} parse: {:.2?}", day, timings.parse);
+                let rows = records
+                    .iter()
+                    .map(|r| {
+                        let s = r.stats;
+                        [
+                            s.day.to_string(),
+                            s.part.to_string(),
+                            s.runs.to_string(),
+                            format!("{:.2?}", bench::duration(s.min_ns)),
+                            format!("{:.2?}", bench::duration(s.median_ns)),
+                            format!("{:.2?}", bench::duration(s.mean_ns)),
+                            format!("{:.2?}", bench::duration(s.stddev_ns)),
+                            r.change_pct
+                                .map_or_else(|| "-".into(), |c| format!("{:+.1}%", c)),
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                report::table(
+                    [
+                        "Day", "Part", "Runs", "Min", "Median", "Mean", "Stddev", "Change",
+                    ],
+                    &rows,
+                );
+            } else {
+                report::records(opts.format, &records)?;
+            }
+            if let Some(save) = save {
+                bench::save(save, &stats)?;
             }
         }
-    } else {
-        let err = format!("Unknown Test (Day {} Part {})", opts.test, opts.part);
-        return Err(std::io::Error::new(std::io::ErrorKind::Other, err));
+        Cmd::New { day } => {
+            if sols.contains_key(&(year, day)) {
+                let err = format!("Year {} Day {} already exists", year, day);
+                return Err(std::io::Error::other(err));
+            }
+            let input = input_path(year, day);
+            let module = scaffold::new_day("src", &input, year, day)?;
+            println!("Created {} and {}", module.display(), input.display());
+        }
+        Cmd::Route {
+            part,
+            input,
+            example,
+            output,
+            scale,
+        } => {
+            let input = match example {
+                Some(name) => InputSource::Example(name),
+                None => input_source(input)?,
+            };
+            let map =
+                day15::Solution::parse(&input).map_err(|e| std::io::Error::other(e.to_string()))?;
+            if !(1..=2).contains(&part) {
+                return Err(std::io::Error::other(format!("Unknown part {}", part)));
+            }
+            let (map, route) = day15::route(&map, part);
+
+            let Some(output) = output else {
+                print!("{}", render::ansi(&map, &route.points));
+                println!("Total risk {}", route.cost);
+                return Ok(());
+            };
+            let draw = match output.extension().and_then(|e| e.to_str()) {
+                Some("ppm") => render::ppm,
+                Some("png") => render::png,
+                _ => {
+                    let err = format!("Unknown image type {}, use .ppm or .png", output.display());
+                    return Err(std::io::Error::other(err));
+                }
+            };
+            let file = std::io::BufWriter::new(std::fs::File::create(&output)?);
+            draw(&map, &route.points, scale, file)?;
+            println!("Total risk {}, drawn to {}", route.cost, output.display());
+        }
+        Cmd::Bits {
+            cmd:
+                BitsCmd::Decode {
+                    hex,
+                    notation,
+                    plain,
+                    strict,
+                },
+        } => {
+            let mode = if strict { Mode::Strict } else { Mode::Lenient };
+            let packet = Packet::from_hex(hex.trim(), mode)
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            println!("{}", packet.expression(notation, !plain));
+            // However big it gets.
+            let value = packet
+                .big_value()
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            println!("Value {}", value);
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc2021::error::DayError;
+    use aoc2021::Answer;
+
+    /// Answer every part with how long the day slept for.
+    fn sleep(parts: &[u32], time: Duration) -> Result<Solved, DayError> {
+        std::thread::sleep(time);
+        let parts = parts
+            .iter()
+            .map(|&part| Answer {
+                part,
+                answer: Ok(format!("{:?}", time)),
+                solve: time,
+            })
+            .collect();
+        let parse = Duration::ZERO;
+        Ok(Solved { parse, parts })
+    }
+
+    fn nap(parts: &[u32], _: &InputSource) -> Result<Solved, DayError> {
+        sleep(parts, Duration::from_millis(10))
+    }
+
+    fn long_nap(parts: &[u32], _: &InputSource) -> Result<Solved, DayError> {
+        sleep(parts, Duration::from_secs(2))
+    }
+
+    fn answers(results: &[DayResult]) -> Vec<Result<String, String>> {
+        results
+            .iter()
+            .map(|(solved, _)| match solved {
+                Ok(s) => s.parts[0].answer.clone(),
+                Err(e) => Err(e.clone()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn timeouts() {
+        let input = Arc::new(InputSource::Default);
+        let days = [long_nap as RunFn, nap, long_nap, nap, nap]
+            .map(|f| (f, vec![1]))
+            .to_vec();
+        let timeout = Some(Duration::from_millis(300));
+        let timed_out = || Err("timed out after 300.00ms".to_string());
+        let expected = [
+            timed_out(),
+            Ok("10ms".into()),
+            timed_out(),
+            Ok("10ms".into()),
+            Ok("10ms".into()),
+        ];
+
+        // The days after each timeout still run, and in the order given.
+        for jobs in [1, 2] {
+            let start = Instant::now();
+            let results = run_days(Some(jobs), days.clone(), &input, timeout).unwrap();
+            assert_eq!(answers(&results), expected, "{} jobs", jobs);
+            assert!(start.elapsed() < Duration::from_secs(2), "{} jobs", jobs);
+            // Nothing had the heap to itself while the naps ran on.
+            assert!(results.iter().all(|(_, peak)| peak.is_none()));
+        }
+
+        let results = run_days(Some(1), vec![(nap, vec![1, 2])], &input, timeout).unwrap();
+        assert_eq!(answers(&results), [Ok("10ms".into())]);
+        assert!(results[0].1.is_some());
+        let results = run_days(Some(2), days[1..2].to_vec(), &input, None).unwrap();
+        assert_eq!(answers(&results), [Ok("10ms".into())]);
+        assert_eq!(results[0].1, None);
+    }
+
+    #[test]
+    fn days() {
+        let days = |s: &str| Days::from_str(s).map(|d| d.0);
+        assert_eq!(days("all"), Ok(1..=25));
+        assert_eq!(days("7"), Ok(7..=7));
+        assert_eq!(days("10..=20"), Ok(10..=20));
+        assert_eq!(days("10..20"), Ok(10..=19));
+        assert_eq!(days("1..0"), Err("Range \"1..0\" is empty".into()));
+        assert_eq!(days("5..5"), Err("Range \"5..5\" is empty".into()));
+        assert_eq!(days("5..=4"), Err("Range \"5..=4\" is empty".into()));
+        assert_eq!(days("x"), Err("Invalid day \"x\" in \"x\"".into()));
+    }
+}
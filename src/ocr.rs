@@ -0,0 +1,116 @@
+//! Reading the block letters that some puzzles draw as their answer.
+use std::collections::HashSet;
+
+/// The letters are 4 dots wide with a blank column after each of them.
+const WIDTH: usize = 4;
+const HEIGHT: usize = 6;
+
+/// Every letter that has turned up in a puzzle so far, other than the 5 wide
+/// `Y` which doesn't fit the spacing.
+const GLYPHS: &[(char, [&str; HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Read the letters drawn by the `(x, y)` dots, starting from the top left
+/// corner at `(0, 0)`. `None` if any of them isn't a known glyph.
+pub fn read(dots: &HashSet<(usize, usize)>) -> Option<String> {
+    let right = dots.iter().map(|(x, _)| *x).max()?;
+    let gap = |x: usize| x % (WIDTH + 1) == WIDTH;
+    if dots.iter().any(|(x, y)| *y >= HEIGHT || gap(*x)) {
+        return None;
+    }
+
+    (0..=right / (WIDTH + 1))
+        .map(|i| {
+            let x0 = i * (WIDTH + 1);
+            GLYPHS
+                .iter()
+                .find(|(_, rows)| {
+                    rows.iter().enumerate().all(|(y, row)| {
+                        row.chars()
+                            .enumerate()
+                            .all(|(x, c)| (c == '#') == dots.contains(&(x0 + x, y)))
+                    })
+                })
+                .map(|(c, _)| *c)
+        })
+        .collect()
+}
+
+/// Draw the dots with `#`, for when they can't be read.
+pub fn render(dots: &HashSet<(usize, usize)>) -> String {
+    let width = dots.iter().map(|(x, _)| *x).max().unwrap_or(0);
+    let height = dots.iter().map(|(_, y)| *y).max().unwrap_or(0);
+
+    let mut s = String::new();
+    for y in 0..=height {
+        for x in 0..=width {
+            s.push(if dots.contains(&(x, y)) { '#' } else { '.' })
+        }
+        s.push('\n')
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dots(picture: &str) -> HashSet<(usize, usize)> {
+        picture
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test() {
+        let picture = "#..#.####.#....####.#..#...##.###..#..#\n\
+                       #..#....#.#....#....#..#....#.#..#.#.#.\n\
+                       ####...#..#....###..####....#.#..#.##..\n\
+                       #..#..#...#....#....#..#....#.###..#.#.\n\
+                       #..#.#....#....#....#..#.#..#.#.#..#.#.\n\
+                       #..#.####.####.####.#..#..##..#..#.#..#\n";
+        let paper = dots(picture);
+        assert_eq!(read(&paper).as_deref(), Some("HZLEHJRK"));
+        assert_eq!(render(&paper), picture);
+
+        // Every glyph reads back as itself.
+        for (c, rows) in GLYPHS {
+            assert_eq!(read(&dots(&rows.join("\n"))), Some(c.to_string()));
+        }
+
+        let square = dots("#####\n#...#\n#...#\n#...#\n#####\n");
+        assert_eq!(read(&square), None);
+
+        // A stray dot between letters, or after the last of them.
+        let mut stray = paper.clone();
+        stray.insert((9, 2));
+        assert_eq!(read(&stray), None);
+        let mut stray = paper.clone();
+        stray.insert((39, 5));
+        assert_eq!(read(&stray), None);
+    }
+}
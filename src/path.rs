@@ -0,0 +1,123 @@
+//! Cheapest paths across a grid, stepping between orthogonal neighbours.
+use crate::grid::ORTHOGONAL;
+use cgmath::Point2;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// A cheapest route, found by [`dijkstra`] or [`astar`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// The total cost of every step, which doesn't include the start.
+    pub cost: usize,
+    /// Every point from the start to the goal, both included.
+    pub points: Vec<Point2<i32>>,
+}
+
+/// The cheapest path from `start` to `goal`, where `cost(p)` is the cost of
+/// stepping onto `p`, or `None` if `p` is a wall or off the grid.
+pub fn dijkstra<F>(start: Point2<i32>, goal: Point2<i32>, cost: F) -> Option<Path>
+where
+    F: Fn(Point2<i32>) -> Option<usize>,
+{
+    search(start, goal, cost, |_| 0)
+}
+
+/// Like [`dijkstra`], but searching towards `goal` first by the Manhattan
+/// distance to it. This only finds the cheapest path when every step costs
+/// at least one.
+pub fn astar<F>(start: Point2<i32>, goal: Point2<i32>, cost: F) -> Option<Path>
+where
+    F: Fn(Point2<i32>) -> Option<usize>,
+{
+    search(start, goal, cost, |p| {
+        ((goal.x - p.x).unsigned_abs() + (goal.y - p.y).unsigned_abs()) as usize
+    })
+}
+
+/// A* with the lower bound `heuristic` on the cost left to reach `goal`.
+fn search<F, H>(start: Point2<i32>, goal: Point2<i32>, cost: F, heuristic: H) -> Option<Path>
+where
+    F: Fn(Point2<i32>) -> Option<usize>,
+    H: Fn(Point2<i32>) -> usize,
+{
+    // Points are kept as tuples in the queue, as `Point2` isn't `Ord`.
+    let mut queue = BinaryHeap::from([Reverse((heuristic(start), 0, (start.x, start.y)))]);
+    let mut dist = HashMap::from([(start, 0)]);
+    let mut prev = HashMap::new();
+
+    while let Some(Reverse((_, d, (x, y)))) = queue.pop() {
+        let u = Point2::new(x, y);
+        if u == goal {
+            let mut points = vec![goal];
+            while let Some(p) = prev.get(points.last().unwrap()) {
+                points.push(*p);
+            }
+            points.reverse();
+            return Some(Path { cost: d, points });
+        }
+        // Skip anything already reached more cheaply since it was queued.
+        if d > dist[&u] {
+            continue;
+        }
+
+        for v in ORTHOGONAL.iter().map(|step| u + step) {
+            let Some(c) = cost(v) else {
+                continue;
+            };
+            let alt = d + c;
+            if dist.get(&v).is_none_or(|d| alt < *d) {
+                dist.insert(v, alt);
+                prev.insert(v, u);
+                queue.push(Reverse((alt + heuristic(v), alt, (v.x, v.y))));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn test() {
+        // `#` is a wall.
+        let grid = Grid::from_chars("1911\n1#91\n1111", |c| match c {
+            '#' => Ok(None),
+            c => c
+                .to_digit(10)
+                .map(|d| Some(d as usize))
+                .ok_or("expected a digit"),
+        })
+        .unwrap();
+        let cost = |p| grid.get(p).copied().flatten();
+        let (start, goal) = (Point2::new(0, 0), Point2::new(3, 0));
+
+        let path = dijkstra(start, goal, cost).unwrap();
+        assert_eq!(path.cost, 7);
+        assert_eq!(
+            path.points,
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (3, 1),
+                (3, 0)
+            ]
+            .map(Point2::from)
+        );
+        assert_eq!(astar(start, goal, cost), Some(path));
+        assert_eq!(
+            dijkstra(start, start, cost),
+            Some(Path {
+                cost: 0,
+                points: vec![start]
+            })
+        );
+        assert_eq!(astar(start, Point2::new(1, 1), cost), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Drawing a grid of costs with a path across it, for looking at the routes
+//! found by [`path`](crate::path).
+use crate::grid::Grid;
+use cgmath::Point2;
+use std::collections::HashSet;
+use std::io::{Result as IoResult, Write};
+
+const BOLD_RED: &str = "\x1b[1;31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// The path drawn over the costs in an image.
+const PATH_COLOUR: [u8; 3] = [255, 48, 32];
+
+/// The digits of `grid` with `path` in bold red on a dimmed background, for
+/// printing to a terminal.
+pub fn ansi(grid: &Grid<usize>, path: &[Point2<i32>]) -> String {
+    let path = path.iter().collect::<HashSet<_>>();
+    let mut s = String::new();
+    for (p, cost) in grid.iter() {
+        let colour = if path.contains(&p) { BOLD_RED } else { DIM };
+        s += &format!("{}{}{}", colour, cost, RESET);
+        if p.x as usize + 1 == grid.width() {
+            s.push('\n');
+        }
+    }
+    s
+}
+
+/// RGB pixels row by row, each cell a `scale` by `scale` square. Costs are
+/// shades of grey from white for 1 to black for 9 and over.
+fn pixels(grid: &Grid<usize>, path: &[Point2<i32>], scale: usize) -> Vec<u8> {
+    let path = path.iter().collect::<HashSet<_>>();
+    let (width, height) = (grid.width() * scale, grid.height() * scale);
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let p = Point2::new((x / scale) as i32, (y / scale) as i32);
+            if path.contains(&p) {
+                pixels.extend(PATH_COLOUR);
+            } else {
+                let shade = 255 - (grid[p].clamp(1, 9) - 1) as u8 * 28;
+                pixels.extend([shade; 3]);
+            }
+        }
+    }
+    pixels
+}
+
+/// A binary PPM image, see [`png`].
+pub fn ppm<W: Write>(
+    grid: &Grid<usize>,
+    path: &[Point2<i32>],
+    scale: usize,
+    mut w: W,
+) -> IoResult<()> {
+    let (width, height) = (grid.width() * scale, grid.height() * scale);
+    write!(w, "P6\n{} {}\n255\n", width, height)?;
+    w.write_all(&pixels(grid, path, scale))
+}
+
+/// A PNG image of `grid` with `path` drawn over it, each cell a `scale` by
+/// `scale` square.
+pub fn png<W: Write>(grid: &Grid<usize>, path: &[Point2<i32>], scale: usize, w: W) -> IoResult<()> {
+    let (width, height) = (grid.width() * scale, grid.height() * scale);
+    let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels(grid, path, scale))?;
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test() {
+        let grid =
+            Grid::from_chars("19\n11", |c| c.to_digit(10).map(|d| d as usize).ok_or("")).unwrap();
+        let path = [(0, 0), (0, 1), (1, 1)].map(Point2::from);
+
+        assert_eq!(
+            ansi(&grid, &path),
+            "\x1b[1;31m1\x1b[0m\x1b[2m9\x1b[0m\n\x1b[1;31m1\x1b[0m\x1b[1;31m1\x1b[0m\n"
+        );
+
+        let mut image = vec![];
+        ppm(&grid, &path, 2, &mut image).unwrap();
+        assert!(image.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(image.len(), "P6\n4 4\n255\n".len() + 4 * 4 * 3);
+        // The top right cell is the riskiest, and not on the path.
+        assert_eq!(image[image.len() - 4 * 4 * 3 + 2 * 3..][..3], [31, 31, 31]);
+
+        let mut image = vec![];
+        png(&grid, &path, 1, &mut image).unwrap();
+        assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Result as IoResult;
+use std::str::FromStr;
+
+/// How results are printed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns for people to read.
+    Table,
+    Json,
+    Csv,
+    /// The Test Anything Protocol, with each record as a YAML block.
+    Tap,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "tap" => Ok(Format::Tap),
+            _ => Err(format!("Unknown format {:?}", s)),
+        }
+    }
+}
+
+pub fn table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(|h| h.len());
+    for row in rows {
+        for (w, col) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(col.chars().count());
+        }
+    }
+
+    let header = header.map(String::from);
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(col, w)| format!("{:<w$}", col, w = w))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Print `records` in one of the machine readable formats. Every record is
+/// expected to serialize to an object with the same fields, in order.
+pub fn records<T: Serialize>(format: Format, records: &[T]) -> IoResult<()> {
+    let records = records
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    let out = match format {
+        Format::Table => unreachable!("tables are laid out by each command"),
+        Format::Json => serde_json::to_string_pretty(&records)? + "\n",
+        Format::Csv => csv(&records),
+        Format::Tap => tap(&records),
+    };
+    print!("{}", out);
+    Ok(())
+}
+
+fn fields(record: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    record.as_object().into_iter().flatten()
+}
+
+fn csv(records: &[Value]) -> String {
+    let cell = |v: &Value| match v {
+        Value::Null => String::new(),
+        Value::String(s) if s.contains(['"', ',', '\n']) => {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        }
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    };
+
+    let mut out = String::new();
+    if let Some(first) = records.first() {
+        out += &fields(first)
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        out += "\n";
+    }
+    for r in records {
+        out += &fields(r)
+            .map(|(_, v)| cell(v))
+            .collect::<Vec<_>>()
+            .join(",");
+        out += "\n";
+    }
+    out
+}
+
+/// A record is a passing test when its `status` is `ok`, or when it has no
+/// status at all. A record without an answer to check is skipped.
+fn tap(records: &[Value]) -> String {
+    let mut out = format!("TAP version 13\n1..{}\n", records.len());
+    for (i, r) in records.iter().enumerate() {
+        let name = format!("{} day {} part {}", r["year"], r["day"], r["part"]);
+        let line = match r.get("status").and_then(Value::as_str) {
+            None | Some("ok") => format!("ok {} - {}", i + 1, name),
+            Some("no answer") => format!("ok {} - {} # SKIP no answer", i + 1, name),
+            Some(_) => format!("not ok {} - {}", i + 1, name),
+        };
+        out += &line;
+        out += "\n  ---\n";
+        for (k, v) in fields(r).filter(|(_, v)| !v.is_null()) {
+            out += &format!("  {}: {}\n", k, v);
+        }
+        out += "  ...\n";
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test() {
+        let records = [
+            serde_json::json!({"year": 2021, "day": 1, "part": 1, "status": "ok", "answer": "1,2"}),
+            serde_json::json!({"year": 2021, "day": 1, "part": 2, "status": "failed", "answer": null}),
+        ];
+        assert_eq!(
+            csv(&records),
+            "year,day,part,status,answer\n2021,1,1,ok,\"1,2\"\n2021,1,2,failed,\n"
+        );
+        assert_eq!(
+            tap(&records),
+            "TAP version 13\n1..2\n\
+             ok 1 - 2021 day 1 part 1\n  ---\n  year: 2021\n  day: 1\n  part: 1\n  status: \"ok\"\n  answer: \"1,2\"\n  ...\n\
+             not ok 2 - 2021 day 1 part 2\n  ---\n  year: 2021\n  day: 1\n  part: 2\n  status: \"failed\"\n  ...\n"
+        );
+    }
+}
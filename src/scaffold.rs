@@ -0,0 +1,157 @@
+use aoc2021::DEFAULT_YEAR;
+use std::fs::OpenOptions;
+use std::io::{Error, Result as IoResult, Write};
+use std::path::{Path, PathBuf};
+
+const TEMPLATE: &str = include_str!("template.rs");
+
+/// Create `dayNN.rs` from the template, an empty input file, and add the new
+/// module to the `tests!` registry in `src/lib.rs`. Days of the default year
+/// go in `src/`, others in `src/yearNNNN/`. Returns the new module's path.
+pub fn new_day<P, Q>(src: P, input: Q, year: u32, day: u32) -> IoResult<PathBuf>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let (src, input) = (src.as_ref(), input.as_ref());
+    let module = format!("day{:02}", day);
+    let (group, dir) = if year == DEFAULT_YEAR {
+        (None, src.to_path_buf())
+    } else {
+        let group = format!("year{}", year);
+        let dir = src.join(&group);
+        (Some(group), dir)
+    };
+
+    let lib = std::fs::read_to_string(src.join("lib.rs"))?;
+    let lib = register(&lib, group.as_deref(), &module)
+        .ok_or_else(|| Error::other(format!("Unable to register {} in lib.rs", module)))?;
+
+    let mut decl = format!("const DAY: u32 = {};", day);
+    if year != DEFAULT_YEAR {
+        decl = format!("const YEAR: u32 = {};\n    {}", year, decl);
+    }
+    let code = TEMPLATE.replace("const DAY: u32 = 9999;", &decl);
+    let path = dir.join(&module).with_extension("rs");
+    std::fs::create_dir_all(&dir)?;
+    // `create_new` so that an existing day is never clobbered.
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?
+        .write_all(code.as_bytes())?;
+    std::fs::write(src.join("lib.rs"), lib)?;
+
+    if let Some(dir) = input.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Keep any input that was already downloaded.
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(input)?;
+    Ok(path)
+}
+
+/// Add `module` to the `tests!` invocation in `lib`, either to the default
+/// year or to the `group` of another year. Lists are kept sorted (with
+/// `template` first) and wrapped the way rustfmt would.
+fn register(lib: &str, group: Option<&str>, module: &str) -> Option<String> {
+    let start = lib.find("\ntests!(")? + "\ntests!(".len();
+    let end = start + lib[start..].find(");")?;
+
+    let list = |s: &str| -> Vec<String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let mut sections = lib[start..end].split(';');
+    let mut default = list(sections.next()?);
+    let mut groups = sections
+        .map(|s| {
+            let (name, rest) = s.split_once('{')?;
+            Some((
+                name.trim().to_string(),
+                list(rest.trim().strip_suffix('}')?),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let modules = match group {
+        None => &mut default,
+        Some(group) => {
+            if !groups.iter().any(|(name, _)| name == group) {
+                groups.push((group.to_string(), vec![]));
+                groups.sort();
+            }
+            &mut groups.iter_mut().find(|(name, _)| name == group)?.1
+        }
+    };
+    if modules.iter().any(|m| m == module) {
+        return None;
+    }
+    modules.push(module.to_string());
+    modules.sort_by(|a, b| (a != "template", a).cmp(&(b != "template", b)));
+
+    let mut body = wrap(&default, 4);
+    for (name, modules) in &groups {
+        body += &format!(";\n    {} {{\n{}\n    }}", name, wrap(modules, 8));
+    }
+    Some(format!("{}\n{}\n{}", &lib[..start], body, &lib[end..]))
+}
+
+/// Comma separate `modules` into lines of at most 99 columns.
+fn wrap(modules: &[String], indent: usize) -> String {
+    let indent = " ".repeat(indent - 1);
+    let mut list = String::new();
+    let mut line = indent.clone();
+    for (i, m) in modules.iter().enumerate() {
+        let m = if i + 1 < modules.len() {
+            format!("{},", m)
+        } else {
+            m.to_string()
+        };
+        if line.len() + 1 + m.len() >= 100 {
+            list += &line;
+            list += "\n";
+            line = indent.clone();
+        }
+        line += " ";
+        line += &m;
+    }
+    list + &line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test() {
+        let lib = "mod x;\n\ntests!(\n    template, day01, day03\n);\n";
+        assert_eq!(
+            register(lib, None, "day02").unwrap(),
+            "mod x;\n\ntests!(\n    template, day01, day02, day03\n);\n"
+        );
+        assert_eq!(register(lib, None, "day03"), None);
+
+        let lib = register(lib, Some("year2022"), "day02").unwrap();
+        assert_eq!(
+            lib,
+            "mod x;\n\ntests!(\n    template, day01, day03;\n    year2022 {\n        day02\n    }\n);\n"
+        );
+        let lib = register(&lib, Some("year2022"), "day01").unwrap();
+        assert!(lib.contains("year2022 {\n        day01, day02\n    }"));
+        assert_eq!(register(&lib, Some("year2022"), "day02"), None);
+
+        // Putting a day back must reproduce lib.rs exactly, wrapping included.
+        let lib = std::fs::read_to_string("src/lib.rs").unwrap();
+        let removed = lib.replacen(", day25", "", 1);
+        assert_eq!(register(&removed, None, "day25").unwrap(), lib);
+        assert!(register(&lib, None, "day26")
+            .unwrap()
+            .contains("day25,\n    day26\n);"));
+    }
+}
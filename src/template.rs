@@ -1,7 +1,7 @@
+use crate::error::DayError;
 use crate::Day;
 #[allow(unused_imports)]
 use std::collections::*;
-use std::io::Result as IoResult;
 
 pub struct Solution {}
 impl Day for Solution {
@@ -10,7 +10,7 @@ impl Day for Solution {
     type Sol1 = usize;
     type Sol2 = usize;
 
-    fn process_input<R>(_r: R) -> IoResult<Self::Input>
+    fn process_input<R>(_r: R) -> Result<Self::Input, DayError>
     where
         R: std::io::BufRead,
     {